@@ -0,0 +1,244 @@
+use std::{ffi::OsString, fmt, path::PathBuf};
+
+use crate::camera::ClampPolicy;
+use crate::minimap::MinimapCorner;
+use crate::zoomer::{ZoomMode, DEFAULT_BACKGROUND_COLOR, DEFAULT_MINIMAP_SIZE};
+
+pub const USAGE: &str =
+    "usage: zoomer [--mode overlay|monitor|window] [--clamp none|visible|inside] [--minimap top-left|top-right|bottom-left|bottom-right] [--minimap-size <pixels>] [--export-dir <directory>] [--background <RRGGBB>] [--render <png>] [image]";
+
+/// Command line arguments.
+#[derive(Debug, PartialEq)]
+pub struct Args {
+    /// An image to show instead of a capture of the screen.
+    pub image_path: Option<PathBuf>,
+    pub zoom_mode: ZoomMode,
+    pub clamp_policy: ClampPolicy,
+    /// Where the minimap is shown, if it is shown from the start.
+    pub minimap_corner: Option<MinimapCorner>,
+    /// Length of the longer side of the minimap in pixels.
+    pub minimap_size: u32,
+    /// Where exported captures and views are saved.
+    pub export_dir: PathBuf,
+    /// RGBA color around the capture and of the parts of the virtual desktop no monitor covers.
+    pub background_color: [u8; 4],
+    /// Where to save a frame rendered offscreen instead of opening the zoomer, needs the `headless` feature.
+    pub render_path: Option<PathBuf>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ArgsError {
+    MissingValue(&'static str),
+    InvalidValue(&'static str, OsString),
+    UnknownOption(OsString),
+    UnexpectedArgument(OsString),
+}
+
+impl fmt::Display for ArgsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ArgsError::MissingValue(option) => write!(f, "{} expects a value", option),
+            ArgsError::InvalidValue(option, value) => {
+                write!(f, "invalid {} `{}`", option, value.to_string_lossy())
+            }
+            ArgsError::UnknownOption(option) => {
+                write!(f, "unknown option `{}`", option.to_string_lossy())
+            }
+            ArgsError::UnexpectedArgument(argument) => {
+                write!(f, "unexpected argument `{}`", argument.to_string_lossy())
+            }
+        }
+    }
+}
+
+impl Args {
+    /// Parses the arguments following the program name.
+    pub fn parse(args: impl IntoIterator<Item = OsString>) -> Result<Self, ArgsError> {
+        let mut args = args.into_iter();
+
+        let mut image_path = None;
+        let mut zoom_mode = ZoomMode::Overlay;
+        let mut clamp_policy = ClampPolicy::KeepAnyPartVisible;
+        let mut minimap_corner = None;
+        let mut minimap_size = DEFAULT_MINIMAP_SIZE;
+        let mut export_dir = PathBuf::from(".");
+        let mut background_color = DEFAULT_BACKGROUND_COLOR;
+        let mut render_path = None;
+
+        while let Some(arg) = args.next() {
+            if arg == "--mode" {
+                let value = args.next().ok_or(ArgsError::MissingValue("--mode"))?;
+
+                zoom_mode = match value.to_str() {
+                    Some("overlay") => ZoomMode::Overlay,
+                    Some("monitor") => ZoomMode::Monitor,
+                    Some("window") => ZoomMode::Window,
+                    _ => return Err(ArgsError::InvalidValue("--mode", value)),
+                };
+            } else if arg == "--clamp" {
+                let value = args.next().ok_or(ArgsError::MissingValue("--clamp"))?;
+
+                clamp_policy = match value.to_str() {
+                    Some("none") => ClampPolicy::None,
+                    Some("visible") => ClampPolicy::KeepAnyPartVisible,
+                    Some("inside") => ClampPolicy::KeepViewInsideImage,
+                    _ => return Err(ArgsError::InvalidValue("--clamp", value)),
+                };
+            } else if arg == "--minimap" {
+                let value = args.next().ok_or(ArgsError::MissingValue("--minimap"))?;
+
+                minimap_corner = Some(match value.to_str() {
+                    Some("top-left") => MinimapCorner::TopLeft,
+                    Some("top-right") => MinimapCorner::TopRight,
+                    Some("bottom-left") => MinimapCorner::BottomLeft,
+                    Some("bottom-right") => MinimapCorner::BottomRight,
+                    _ => return Err(ArgsError::InvalidValue("--minimap", value)),
+                });
+            } else if arg == "--minimap-size" {
+                let value = args
+                    .next()
+                    .ok_or(ArgsError::MissingValue("--minimap-size"))?;
+
+                minimap_size = match value.to_str().and_then(|value| value.parse().ok()) {
+                    Some(size) if size > 0 => size,
+                    _ => return Err(ArgsError::InvalidValue("--minimap-size", value)),
+                };
+            } else if arg == "--export-dir" {
+                export_dir = args
+                    .next()
+                    .map(PathBuf::from)
+                    .ok_or(ArgsError::MissingValue("--export-dir"))?;
+            } else if arg == "--background" {
+                let value = args.next().ok_or(ArgsError::MissingValue("--background"))?;
+
+                background_color = parse_hex_color(&value)
+                    .ok_or(ArgsError::InvalidValue("--background", value))?;
+            } else if arg == "--render" {
+                render_path = Some(
+                    args.next()
+                        .map(PathBuf::from)
+                        .ok_or(ArgsError::MissingValue("--render"))?,
+                );
+            } else if arg.to_string_lossy().starts_with("--") {
+                return Err(ArgsError::UnknownOption(arg));
+            } else if image_path.is_none() {
+                image_path = Some(PathBuf::from(arg));
+            } else {
+                return Err(ArgsError::UnexpectedArgument(arg));
+            }
+        }
+
+        Ok(Self {
+            image_path,
+            zoom_mode,
+            clamp_policy,
+            minimap_corner,
+            minimap_size,
+            export_dir,
+            background_color,
+            render_path,
+        })
+    }
+}
+
+/// Parses an opaque `RRGGBB` color, optionally prefixed with `#`.
+fn parse_hex_color(value: &OsString) -> Option<[u8; 4]> {
+    let value = value.to_str()?;
+    let value = value.strip_prefix('#').unwrap_or(value);
+
+    // `from_str_radix` would also accept a sign.
+    if value.len() != 6 || !value.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let rgb = u32::from_str_radix(value, 16).ok()?;
+
+    Some([(rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8, 0xFF])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, ArgsError> {
+        Args::parse(args.iter().map(OsString::from))
+    }
+
+    #[test]
+    fn defaults() {
+        assert_eq!(
+            parse(&[]),
+            Ok(Args {
+                image_path: None,
+                zoom_mode: ZoomMode::Overlay,
+                clamp_policy: ClampPolicy::KeepAnyPartVisible,
+                minimap_corner: None,
+                minimap_size: DEFAULT_MINIMAP_SIZE,
+                export_dir: PathBuf::from("."),
+                background_color: DEFAULT_BACKGROUND_COLOR,
+                render_path: None,
+            })
+        );
+    }
+
+    #[test]
+    fn all_options() {
+        assert_eq!(
+            parse(&[
+                "diagram.png",
+                "--mode",
+                "monitor",
+                "--clamp",
+                "inside",
+                "--minimap",
+                "top-left",
+                "--minimap-size",
+                "320",
+                "--export-dir",
+                "exports",
+                "--background",
+                "#1E90ff",
+                "--render",
+                "view.png"
+            ]),
+            Ok(Args {
+                image_path: Some(PathBuf::from("diagram.png")),
+                zoom_mode: ZoomMode::Monitor,
+                clamp_policy: ClampPolicy::KeepViewInsideImage,
+                minimap_corner: Some(MinimapCorner::TopLeft),
+                minimap_size: 320,
+                export_dir: PathBuf::from("exports"),
+                background_color: [0x1E, 0x90, 0xFF, 0xFF],
+                render_path: Some(PathBuf::from("view.png")),
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_arguments() {
+        assert_eq!(
+            parse(&["--export-dir"]),
+            Err(ArgsError::MissingValue("--export-dir"))
+        );
+        assert_eq!(
+            parse(&["--mode", "fullscreen"]),
+            Err(ArgsError::InvalidValue("--mode", "fullscreen".into()))
+        );
+        assert_eq!(
+            parse(&["--minimap-size", "0"]),
+            Err(ArgsError::InvalidValue("--minimap-size", "0".into()))
+        );
+        assert_eq!(
+            parse(&["--background", "+1E90F"]),
+            Err(ArgsError::InvalidValue("--background", "+1E90F".into()))
+        );
+        assert_eq!(
+            parse(&["--zoom"]),
+            Err(ArgsError::UnknownOption("--zoom".into()))
+        );
+        assert_eq!(
+            parse(&["a.png", "b.png"]),
+            Err(ArgsError::UnexpectedArgument("b.png".into()))
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use crate::camera::CameraView;
+
+/// Number of bookmarks, one for each of the keys 1 to 9.
+pub const SLOT_COUNT: usize = 9;
+
+pub struct Bookmark {
+    pub name: String,
+    pub view: CameraView,
+}
+
+/// Camera bookmarks, kept until the zoomer exits.
+pub struct Bookmarks {
+    slots: [Option<Bookmark>; SLOT_COUNT],
+}
+
+impl Bookmarks {
+    pub fn new() -> Self {
+        Self {
+            slots: Default::default(),
+        }
+    }
+
+    /// Saves `view` in the 1-based `slot`, keeping the name of the bookmark it replaces.
+    pub fn save(&mut self, slot: usize, view: CameraView) {
+        let bookmark = &mut self.slots[slot - 1];
+
+        match bookmark {
+            Some(bookmark) => bookmark.view = view,
+            None => {
+                *bookmark = Some(Bookmark {
+                    name: format!("Bookmark {}", slot),
+                    view,
+                })
+            }
+        }
+    }
+
+    pub fn get(&self, slot: usize) -> Option<&Bookmark> {
+        self.slots[slot - 1].as_ref()
+    }
+
+    pub fn remove(&mut self, slot: usize) {
+        self.slots[slot - 1] = None;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.iter().all(Option::is_none)
+    }
+
+    /// Iterates over the saved bookmarks and their 1-based slots.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (usize, &mut Bookmark)> {
+        self.slots
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, bookmark)| Some((index + 1, bookmark.as_mut()?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra_glm::vec2;
+
+    use super::*;
+
+    fn view(zoom_factor: f32) -> CameraView {
+        CameraView {
+            center: vec2(0.0, 0.0),
+            zoom_factor,
+            rotation: 0.0,
+        }
+    }
+
+    #[test]
+    fn overwriting_keeps_the_name() {
+        let mut bookmarks = Bookmarks::new();
+        assert!(bookmarks.is_empty());
+
+        bookmarks.save(3, view(2.0));
+        bookmarks.iter_mut().next().unwrap().1.name = String::from("Terminal");
+        bookmarks.save(3, view(4.0));
+
+        let bookmark = bookmarks.get(3).unwrap();
+        assert_eq!(bookmark.name, "Terminal");
+        assert_eq!(bookmark.view, view(4.0));
+        assert!(bookmarks.get(1).is_none());
+
+        bookmarks.remove(3);
+        assert!(bookmarks.is_empty());
+    }
+}
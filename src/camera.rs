@@ -1,75 +1,495 @@
+use std::collections::VecDeque;
+use std::f32::consts::{PI, TAU};
 use std::ops::RangeInclusive;
 
-use nalgebra_glm::{clamp_vec, vec2_to_vec3, Mat4, Vec2};
+use nalgebra_glm::{clamp_vec, vec2, vec2_to_vec3, vec3, Mat4, Vec2};
 
-use crate::interpolation::{ExponentialSmoothing, InterpolatedScalar, InterpolatedVector};
+use crate::flight::Flight;
+use crate::interpolation::{
+    ExponentialSmoothing, InterpolatedScalar, InterpolatedVector, Logarithmic, SpringDamper,
+};
+
+/// How quickly coasting slows down, the fraction of the velocity lost per second is `1 - e^-FRICTION`.
+const FRICTION: f32 = 5.0;
+/// Friction while the center of the screen is out of the position range, so the camera doesn't coast far past it.
+const OUT_OF_RANGE_FRICTION: f32 = 30.0;
+/// Coasting stops below this speed, in screen space units per second.
+const MIN_COASTING_SPEED: f32 = 0.01;
+/// How stiff the rubber band is when dragging past the range of the center, higher values resist less.
+const RUBBER_BAND_STIFFNESS: f32 = 0.55;
+/// How many views [`Camera::undo`] can go back through.
+const HISTORY_LENGTH: usize = 50;
+/// How close the camera has to get to where it's going to count as settled there, in screen space units for
+/// positions, and in radians and the logarithm of the ratio for rotations and zoom factors.
+const SETTLE_TOLERANCE: f32 = 1e-3;
+
+/// How far the camera may move away from the image.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClampPolicy {
+    /// The camera moves freely.
+    None,
+    /// At least some part of the image stays on the screen.
+    KeepAnyPartVisible,
+    /// The screen stays covered by the image where possible, and the image is centered on the axes it doesn't cover.
+    KeepViewInsideImage,
+}
+
+/// Where a camera is looking.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraView {
+    /// The world space point at the center of the screen.
+    pub center: Vec2,
+    pub zoom_factor: f32,
+    /// Counter-clockwise rotation in radians.
+    pub rotation: f32,
+}
 
 /// A 2D camera.
+///
+/// Instead of interpolating its position, the camera is anchored at a point on the screen and interpolates the world
+/// point shown there. The position follows from the zoom factor and rotation, so zooming and rotating around the anchor
+/// keep the point under it in place for the whole animation, not just once it settles.
 pub struct Camera {
-    /// The position of the camera in camera space.
-    position: InterpolatedVector<f32, 2, ExponentialSmoothing<f32>>,
-    zoom_factor: InterpolatedScalar<f32, ExponentialSmoothing<f32>>,
+    /// The screen space point the camera zooms and rotates around.
+    anchor: Vec2,
+    /// The world space point shown at the `anchor`.
+    anchor_world: InterpolatedVector<f32, 2, ExponentialSmoothing<f32>>,
+    /// Interpolated logarithmically, so every doubling of the magnification takes the same time. A spring keeps
+    /// continuous scrolling smooth, as it carries on with its velocity when the target moves again.
+    zoom_factor: InterpolatedScalar<f32, SpringDamper<f32, 1>, Logarithmic>,
+    /// Counter-clockwise rotation in radians.
+    rotation: InterpolatedScalar<f32, ExponentialSmoothing<f32>>,
+    /// Drives the zoom factor and the center of the screen instead of their interpolators while flying.
+    flight: Option<Flight>,
+    /// Velocity in screen space units per second the camera keeps panning with after being flung.
+    velocity: Vec2,
 
     zoom_range: RangeInclusive<f32>,
+    /// Aspect ratio of the screen. Screen space is stretched to [-1, 1] on both axes, so rotations have to happen in
+    /// a space where both axes have the same scale, or they would shear the image.
+    aspect_ratio: f32,
     /// The range of position in world space.
     position_range: Vec2,
+    clamp_policy: ClampPolicy,
+    /// Views the camera settled at, oldest first.
+    history: VecDeque<CameraView>,
+    /// The index in `history` of the view the camera last settled at, or was sent back or forward to.
+    history_index: usize,
 }
 
 impl Camera {
     /// Creates a new camera.
     /// - `zoom_range`: the min and max value of the `zoom_factor`
     /// - `position_range`: the symmetric range of the position in world space (`±position_range.x` on x axis and `±position_range.y` on y axis)
-    pub fn new(zoom_range: RangeInclusive<f32>, position_range: Vec2) -> Self {
+    /// - `aspect_ratio`: the aspect ratio of the screen
+    pub fn new(zoom_range: RangeInclusive<f32>, position_range: Vec2, aspect_ratio: f32) -> Self {
         const LENGTH: f32 = 0.5;
         const RATE: f32 = 2.5;
+        const ZOOM_FREQUENCY: f32 = 2.5;
 
-        Self {
-            position: InterpolatedVector::new_zeroed(ExponentialSmoothing::new(LENGTH, RATE)),
-            zoom_factor: InterpolatedScalar::new(1.0, ExponentialSmoothing::new(LENGTH, RATE)),
+        let mut camera = Self {
+            anchor: Vec2::zeros(),
+            anchor_world: InterpolatedVector::new_zeroed(ExponentialSmoothing::new(LENGTH, RATE)),
+            zoom_factor: InterpolatedScalar::new_mapped(
+                1.0,
+                SpringDamper::critically_damped(ZOOM_FREQUENCY),
+            ),
+            rotation: InterpolatedScalar::new_zeroed(ExponentialSmoothing::new(LENGTH, RATE)),
+            flight: None,
+            velocity: Vec2::zeros(),
             zoom_range,
             position_range,
-        }
+            aspect_ratio,
+            clamp_policy: ClampPolicy::KeepAnyPartVisible,
+            history: VecDeque::new(),
+            history_index: 0,
+        };
+        camera.history.push_back(camera.view());
+
+        camera
+    }
+
+    pub fn set_clamp_policy(&mut self, clamp_policy: ClampPolicy) {
+        self.clamp_policy = clamp_policy;
     }
 
     /// Smoothly translates the camera by the given `translation`.
     pub fn translate(&mut self, translation: Vec2) {
-        self.position
-            .set_target(self.position.target() + translation);
+        self.land();
+        self.velocity = Vec2::zeros();
+
+        self.translate_target(translation);
+    }
+
+    /// Keeps panning with `velocity` in screen space units per second, slowing down with friction. Once it stops,
+    /// the camera is clamped like with [`Camera::clamp_me_daddy`].
+    pub fn fling(&mut self, velocity: Vec2) {
+        self.land();
+        self.velocity = velocity;
+
+        if velocity.norm() < MIN_COASTING_SPEED {
+            self.velocity = Vec2::zeros();
+            self.clamp_me_daddy();
+        }
     }
 
+    /// Stops flying or coasting where the camera currently is, eg. when it's grabbed.
+    pub fn stop(&mut self) {
+        self.land();
+
+        if self.velocity != Vec2::zeros() {
+            self.velocity = Vec2::zeros();
+            self.anchor_world.set_target(self.anchor_world.current());
+        }
+    }
+
+    /// Like [`Camera::translate`], but resists more and more the further the center of the screen is dragged out of its
+    /// range, up to half of the screen. Call [`Camera::fling`] or [`Camera::clamp_me_daddy`] when letting go, to spring
+    /// back into the range.
+    pub fn drag(&mut self, translation: Vec2) {
+        let center_range = match self.center_range() {
+            Some(center_range) => center_range,
+            None => return self.translate(translation),
+        };
+
+        self.land();
+        self.velocity = Vec2::zeros();
+
+        let center = self.target_screen_to_world_space(vec2(0.0, 0.0));
+        let limit = self.view_half_extent();
+        let world_translation =
+            self.rotate(translation, -self.rotation.target()) / self.zoom_factor.target();
+
+        // Works out where the center would be without the resistance, so it stays the same whichever way it's dragged.
+        let new_center = Vec2::from_fn(|axis, _| {
+            let (range, limit) = (center_range[axis], limit[axis]);
+            let unresisted = unstretch(center[axis], range, limit) - world_translation[axis];
+
+            stretch(unresisted, range, limit)
+        });
+
+        self.anchor_world
+            .set_target(self.anchor_world.target() + new_center - center);
+    }
+
+    fn translate_target(&mut self, translation: Vec2) {
+        // The world moves with the translation, so the point shown at the anchor moves against it.
+        let world_translation =
+            self.rotate(translation, -self.rotation.target()) / self.zoom_factor.target();
+
+        self.anchor_world
+            .set_target(self.anchor_world.target() - world_translation);
+    }
+
+    /// Smoothly moves the camera back within the bounds of its [`ClampPolicy`].
     pub fn clamp_me_daddy(&mut self) {
-        let camera_position_range = self.world_to_camera_space(self.position_range);
+        self.land();
 
-        self.position.set_target(clamp_vec(
-            &self.position.target(),
-            &-camera_position_range,
-            &camera_position_range,
-        ));
+        let center_range = match self.center_range() {
+            Some(center_range) => center_range,
+            None => return,
+        };
+
+        // Clamped along the world's axes rather than the screen's.
+        let center = self.target_screen_to_world_space(vec2(0.0, 0.0));
+        let clamped_center = clamp_vec(&center, &-center_range, &center_range);
+
+        self.anchor_world
+            .set_target(self.anchor_world.target() + clamped_center - center);
+    }
+
+    /// Returns the symmetric range the center of the screen is kept in by the clamp policy, in world space, once the
+    /// camera settles.
+    fn center_range(&self) -> Option<Vec2> {
+        match self.clamp_policy {
+            ClampPolicy::None => None,
+            ClampPolicy::KeepAnyPartVisible => {
+                Some(self.position_range + self.view_inner_half_extent())
+            }
+            ClampPolicy::KeepViewInsideImage => {
+                Some((self.position_range - self.view_half_extent()).map(|range| range.max(0.0)))
+            }
+        }
+    }
+
+    /// Returns half of the size of the screen's bounding box along the world's axes in world space, once the camera
+    /// settles.
+    fn view_half_extent(&self) -> Vec2 {
+        let (corner, other_corner) = (
+            self.rotate(vec2(1.0, 1.0), -self.rotation.target()).abs(),
+            self.rotate(vec2(1.0, -1.0), -self.rotation.target()).abs(),
+        );
+
+        corner.sup(&other_corner) / self.zoom_factor.target()
+    }
+
+    /// Like [`Camera::view_half_extent`], but of the largest box along the world's axes that fits on the screen, with
+    /// the same aspect ratio as the bounding box. Both are the same unless the camera is rotated.
+    fn view_inner_half_extent(&self) -> Vec2 {
+        let half_extent = self.view_half_extent() * self.zoom_factor.target();
+
+        // How far the corners of the bounding box stick out of the screen, which is 1 by 1 from its center.
+        let overshoot = [half_extent, vec2(half_extent.x, -half_extent.y)]
+            .iter()
+            .map(|&corner| self.rotate(corner, self.rotation.target()).abs().max())
+            .fold(0.0, f32::max);
+
+        half_extent / overshoot / self.zoom_factor.target()
     }
 
     /// Smoothly zooms the camera in by the given zoom factor towards the given point.
     pub fn zoom(&mut self, zoom_multiplier: f32, screen_point: Vec2) {
+        self.land();
+        self.set_anchor(screen_point);
+
         let new_zoom_factor = (self.zoom_factor.target() * zoom_multiplier)
             .clamp(*self.zoom_range.start(), *self.zoom_range.end());
 
-        // Recompute the zoom multiplier as it may have changed due to the clamp.
-        let zoom_multiplier = new_zoom_factor / self.zoom_factor.target();
-
         self.zoom_factor.set_target(new_zoom_factor);
+    }
+
+    /// Smoothly zooms in so that the screen space rectangle between `corner` and `opposite_corner` fills the screen
+    /// as far as its aspect ratio allows, with its center at the center of the screen. If the zoom range doesn't allow
+    /// that much zoom, zooms in as far as it can and still centers the rectangle.
+    pub fn zoom_to_rect(&mut self, corner: Vec2, opposite_corner: Vec2) {
+        let size = (opposite_corner - corner).abs();
+        let center = (corner + opposite_corner) / 2.0;
+
+        // The screen is 2 units wide and tall.
+        let zoom_multiplier = (2.0 / size.x).min(2.0 / size.y);
+        let zoom_multiplier = (self.zoom_factor.target() * zoom_multiplier)
+            .clamp(*self.zoom_range.start(), *self.zoom_range.end())
+            / self.zoom_factor.target();
+
+        if (zoom_multiplier - 1.0).abs() < 1e-3 {
+            self.translate(-center);
+        } else {
+            // Scaling by `zoom_multiplier` around this point moves the center of the rectangle to the center of the
+            // screen, so the point stays in place for the whole animation.
+            let fixed_point = center * zoom_multiplier / (zoom_multiplier - 1.0);
+
+            self.zoom(zoom_multiplier, fixed_point);
+        }
+    }
+
+    /// Smoothly rotates the camera counter-clockwise by `angle` radians around the given point.
+    pub fn rotate_around(&mut self, angle: f32, screen_point: Vec2) {
+        self.land();
+        self.set_anchor(screen_point);
+
+        self.rotation.set_target(self.rotation.target() + angle);
+    }
+
+    /// Returns the view the camera is going to settle at.
+    pub fn view(&self) -> CameraView {
+        CameraView {
+            center: self.target_screen_to_world_space(vec2(0.0, 0.0)),
+            zoom_factor: self.zoom_factor.target(),
+            rotation: self.rotation.target(),
+        }
+    }
+
+    /// Flies the camera to `view` in `duration` seconds, see [`Camera::fly_to`]. Rotates the shorter way around.
+    pub fn fly_to_view(&mut self, view: CameraView, duration: f32) {
+        self.fly_to(view.center, view.zoom_factor, duration);
+        self.turn_to(view.rotation);
+    }
+
+    /// Smoothly moves the camera to `view` like any other zoom or pan, without flying. Rotates the shorter way around.
+    pub fn animate_to_view(&mut self, view: CameraView) {
+        self.land();
+        self.velocity = Vec2::zeros();
+        self.set_anchor(vec2(0.0, 0.0));
+
+        self.anchor_world.set_target(view.center);
+        self.zoom_factor.set_target(
+            view.zoom_factor
+                .clamp(*self.zoom_range.start(), *self.zoom_range.end()),
+        );
+        self.turn_to(view.rotation);
+    }
+
+    /// Smoothly rotates the camera to `rotation` the shorter way around.
+    fn turn_to(&mut self, rotation: f32) {
+        let current_rotation = self.rotation.target();
+        let mut turn = (rotation - current_rotation).rem_euclid(TAU);
+        if turn > PI {
+            turn -= TAU;
+        }
+
+        self.rotation.set_target(current_rotation + turn);
+    }
 
-        // Convert to camera space using the position target, not current position
-        let point = screen_point - self.position.target();
-        self.translate(point - point * zoom_multiplier);
+    /// Flies the camera in `duration` seconds so that `target_position` in world space ends up at the center of the
+    /// screen, zoomed in by `target_zoom`. Zooms out on the way if the two are far apart, see [`Flight`]. Any other
+    /// camera movement cancels the flight.
+    pub fn fly_to(&mut self, target_position: Vec2, target_zoom: f32, duration: f32) {
+        self.land();
+        self.set_anchor(vec2(0.0, 0.0));
+
+        let target_zoom = target_zoom.clamp(*self.zoom_range.start(), *self.zoom_range.end());
+
+        self.flight = Some(Flight::new(
+            self.world_to_square_space(self.anchor_world.current()),
+            Self::view_height(self.zoom_factor.current()),
+            self.world_to_square_space(target_position),
+            Self::view_height(target_zoom),
+            duration,
+        ));
+
+        self.anchor_world.set_target(target_position);
+        self.zoom_factor.set_target(target_zoom);
+    }
+
+    /// Adds the view to the history if the camera has settled at a new one, dropping the views that could be redone.
+    /// Only call this while no gesture is in progress, or views it merely pauses at would be recorded too.
+    pub fn record_view(&mut self) {
+        let view = self.view();
+
+        if !self.is_settled() || views_are_close(&view, &self.history[self.history_index]) {
+            return;
+        }
+
+        self.history.truncate(self.history_index + 1);
+        self.history.push_back(view);
+
+        if self.history.len() > HISTORY_LENGTH {
+            self.history.pop_front();
+        }
+
+        self.history_index = self.history.len() - 1;
+    }
+
+    /// Smoothly goes back to the previously recorded view, or to the last one if the camera has moved away from it
+    /// without settling yet.
+    pub fn undo(&mut self) {
+        if views_are_close(&self.view(), &self.history[self.history_index]) {
+            if self.history_index == 0 {
+                return;
+            }
+
+            self.history_index -= 1;
+        }
+
+        self.animate_to_view(self.history[self.history_index]);
+    }
+
+    /// Smoothly goes forward to the view that was last undone, unless the camera has settled somewhere else since.
+    pub fn redo(&mut self) {
+        if self.history_index + 1 < self.history.len() {
+            self.history_index += 1;
+
+            self.animate_to_view(self.history[self.history_index]);
+        }
+    }
+
+    /// Whether the camera is moving by itself, flying to a view or coasting after a fling.
+    pub fn is_flying_or_coasting(&self) -> bool {
+        self.flight.is_some() || self.velocity != Vec2::zeros()
+    }
+
+    /// Whether the camera has (almost) stopped moving.
+    pub fn is_settled(&self) -> bool {
+        self.flight.is_none()
+            && self.velocity == Vec2::zeros()
+            // The anchor is in world space, where the screen space tolerance shrinks as the camera zooms in.
+            && self
+                .anchor_world
+                .is_finished(SETTLE_TOLERANCE / self.zoom_factor.target())
+            && self.zoom_factor.is_finished(SETTLE_TOLERANCE)
+            && self.rotation.is_finished(SETTLE_TOLERANCE)
+    }
+
+    /// Stops the flight, if any, wherever the camera currently is.
+    fn land(&mut self) {
+        if self.flight.take().is_some() {
+            self.anchor_world.set_target(self.anchor_world.current());
+            self.zoom_factor.set_target(self.zoom_factor.current());
+        }
+    }
+
+    /// Returns the height of the screen in world units at the given zoom factor.
+    fn view_height(zoom_factor: f32) -> f32 {
+        2.0 / zoom_factor
+    }
+
+    /// Moves the anchor to `screen_point` without changing what's on the screen, now or once the camera settles.
+    fn set_anchor(&mut self, screen_point: Vec2) {
+        // Any translation that hasn't finished yet carries on from the new anchor.
+        let pending_translation = self.anchor_world.target() - self.anchor_world.current();
+        let world_point = self.screen_to_world_space(screen_point);
+
+        self.anchor = screen_point;
+        self.anchor_world.set_current(world_point);
+        self.anchor_world
+            .set_target(world_point + pending_translation);
+    }
+
+    /// Updates the aspect ratio of the screen, eg. after the window is resized.
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: f32) {
+        self.aspect_ratio = aspect_ratio;
+    }
+
+    /// Updates the range of the position in world space, eg. after the window is resized or the screenshot changes.
+    pub fn set_position_range(&mut self, position_range: Vec2) {
+        self.position_range = position_range;
     }
 
     pub fn update(&mut self, dt: f32) {
-        self.zoom_factor.update(dt);
-        self.position.update(dt);
+        self.rotation.update(dt);
+
+        match &mut self.flight {
+            Some(flight) => {
+                flight.update(dt);
+
+                let (center, height) = flight.current();
+                let is_finished = flight.is_finished();
+
+                self.anchor_world
+                    .set_current(self.square_to_world_space(center));
+                self.zoom_factor.set_current(Self::view_height(height));
+
+                if is_finished {
+                    self.flight = None;
+                }
+            }
+            None => {
+                if self.velocity != Vec2::zeros() {
+                    self.coast(dt);
+                }
+
+                self.zoom_factor.update(dt);
+                self.anchor_world.update(dt);
+            }
+        }
+    }
+
+    fn coast(&mut self, dt: f32) {
+        let center = self.target_screen_to_world_space(vec2(0.0, 0.0));
+        let friction = match self.center_range() {
+            Some(center_range)
+                if center.x.abs() > center_range.x || center.y.abs() > center_range.y =>
+            {
+                OUT_OF_RANGE_FRICTION
+            }
+            _ => FRICTION,
+        };
+
+        // Integrated exactly, so it doesn't depend on the frame rate.
+        let decay = (-friction * dt).exp();
+        self.translate_target(self.velocity * (1.0 - decay) / friction);
+        self.velocity *= decay;
+
+        if self.velocity.norm() < MIN_COASTING_SPEED {
+            self.velocity = Vec2::zeros();
+            self.clamp_me_daddy();
+        }
     }
 
-    /// Converts from screen space coordinates or NDC ([-1, 1] x [-1, 1]) to camera space coordinates ([`-self.zoom_factor`, `self.zoom_factor`] x [`-self.zoom_factor`, `self.zoom_factor`]).
+    /// Converts from screen space coordinates or NDC ([-1, 1] x [-1, 1]) to camera space coordinates ([`-self.zoom_factor`, `self.zoom_factor`] x [`-self.zoom_factor`, `self.zoom_factor`]), which are aligned with the world's axes.
     pub fn screen_to_camera_space(&self, screen_coords: Vec2) -> Vec2 {
-        screen_coords - self.position.current()
+        self.rotate(screen_coords - self.anchor, -self.rotation.current())
+            + self.anchor_world.current() * self.zoom_factor.current()
     }
 
     /// Converts from screen space coordintes or NDC ([-1, 1] x [-1, 1]) to global world space coordinates
@@ -77,14 +497,51 @@ impl Camera {
         self.screen_to_camera_space(screen_coords) / self.zoom_factor.current()
     }
 
+    /// Like [`Camera::screen_to_world_space`], but for where the camera is going to settle.
+    fn target_screen_to_world_space(&self, screen_coords: Vec2) -> Vec2 {
+        self.anchor_world.target()
+            + self.rotate(screen_coords - self.anchor, -self.rotation.target())
+                / self.zoom_factor.target()
+    }
+
     /// Converts the camera's transformations into an equivalent homogenous matrix.
     pub fn to_homogenous(&self) -> Mat4 {
-        Mat4::new_translation(&vec2_to_vec3(&self.position.current()))
+        let to_square_space = vec3(self.aspect_ratio, 1.0, 1.0);
+
+        Mat4::new_translation(&vec2_to_vec3(&self.screen_position()))
+            * Mat4::new_nonuniform_scaling(&to_square_space.map(|scale| 1.0 / scale))
+            * Mat4::new_rotation(vec3(0.0, 0.0, self.rotation.current()))
+            * Mat4::new_nonuniform_scaling(&to_square_space)
             * Mat4::new_scaling(self.zoom_factor.current())
     }
 
-    fn world_to_camera_space(&self, world_coords: Vec2) -> Vec2 {
-        world_coords * self.zoom_factor.current()
+    /// Returns where the world's origin currently is in screen space.
+    fn screen_position(&self) -> Vec2 {
+        self.anchor
+            - self.rotate(
+                self.anchor_world.current() * self.zoom_factor.current(),
+                self.rotation.current(),
+            )
+    }
+
+    /// Scales a world space vector so that both axes have the same scale on the screen.
+    fn world_to_square_space(&self, world_vector: Vec2) -> Vec2 {
+        vec2(world_vector.x * self.aspect_ratio, world_vector.y)
+    }
+
+    fn square_to_world_space(&self, square_vector: Vec2) -> Vec2 {
+        vec2(square_vector.x / self.aspect_ratio, square_vector.y)
+    }
+
+    /// Rotates a screen space vector counter-clockwise by `angle` radians as it would appear on the screen.
+    fn rotate(&self, screen_vector: Vec2, angle: f32) -> Vec2 {
+        let (sin, cos) = angle.sin_cos();
+        let x = screen_vector.x * self.aspect_ratio;
+
+        vec2(
+            (x * cos - screen_vector.y * sin) / self.aspect_ratio,
+            x * sin + screen_vector.y * cos,
+        )
     }
 
     fn camera_to_world_space(&self, camera_coords: Vec2) -> Vec2 {
@@ -93,10 +550,526 @@ impl Camera {
 
     /// Returns the camera's position in world space.
     pub fn position(&self) -> Vec2 {
-        self.camera_to_world_space(self.position.current())
+        self.camera_to_world_space(self.screen_position())
     }
 
     pub fn zoom_factor(&self) -> f32 {
         self.zoom_factor.current()
     }
+
+    /// Returns the camera's counter-clockwise rotation in radians.
+    pub fn rotation(&self) -> f32 {
+        self.rotation.current()
+    }
+}
+
+/// Whether two views look the same, give or take [`SETTLE_TOLERANCE`].
+fn views_are_close(view: &CameraView, other: &CameraView) -> bool {
+    let turn = (view.rotation - other.rotation).rem_euclid(TAU);
+
+    (view.center - other.center).abs().max() * view.zoom_factor < SETTLE_TOLERANCE
+        && (view.zoom_factor / other.zoom_factor).ln().abs() < SETTLE_TOLERANCE
+        && turn.min(TAU - turn) < SETTLE_TOLERANCE
+}
+
+/// Moves a `value` which is out of `±range` back towards it, by more the further out it is, but never past `limit`
+/// out of it. The same function as the rubber band scrolling on iOS.
+fn stretch(value: f32, range: f32, limit: f32) -> f32 {
+    let overshoot = value.abs() - range;
+
+    if overshoot <= 0.0 {
+        return value;
+    }
+
+    let overshoot = (1.0 - 1.0 / (overshoot * RUBBER_BAND_STIFFNESS / limit + 1.0)) * limit;
+
+    value.signum() * (range + overshoot)
+}
+
+/// The inverse of [`stretch`].
+fn unstretch(value: f32, range: f32, limit: f32) -> f32 {
+    let overshoot = value.abs() - range;
+
+    if overshoot <= 0.0 {
+        return value;
+    }
+
+    // Anything at or past the limit would be infinitely far away.
+    let overshoot = overshoot.min(limit * 0.999);
+    let overshoot = limit / RUBBER_BAND_STIFFNESS * (limit / (limit - overshoot) - 1.0);
+
+    value.signum() * (range + overshoot)
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra_glm::{vec4, Vec4};
+    use proptest::prelude::*;
+
+    use super::*;
+
+    /// Long enough for every interpolation to settle.
+    const SETTLE_TIME: f32 = 10.0;
+
+    fn camera() -> Camera {
+        Camera::new(0.25..=500.0, vec2(1.0, 0.75), 16.0 / 9.0)
+    }
+
+    fn assert_vec2_eq(a: Vec2, b: Vec2) {
+        assert!((a - b).abs().max() < 1e-4, "{} != {}", a, b);
+    }
+
+    fn world_to_screen_space(camera: &Camera, world_coords: Vec2) -> Vec2 {
+        let screen_coords: Vec4 =
+            camera.to_homogenous() * vec4(world_coords.x, world_coords.y, 0.0, 1.0);
+
+        screen_coords.xy()
+    }
+
+    #[test]
+    fn homogenous_matrix_is_the_inverse_of_screen_to_world_space() {
+        let mut camera = camera();
+        camera.zoom(2.5, vec2(0.3, -0.2));
+        camera.rotate_around(0.7, vec2(-0.5, 0.1));
+        camera.translate(vec2(0.1, 0.4));
+        camera.update(SETTLE_TIME);
+
+        for screen_point in [vec2(0.0, 0.0), vec2(1.0, -1.0), vec2(-0.25, 0.6)] {
+            let world_point = camera.screen_to_world_space(screen_point);
+
+            assert_vec2_eq(world_to_screen_space(&camera, world_point), screen_point);
+        }
+    }
+
+    #[test]
+    fn rotation_keeps_point_in_place_and_preserves_screen_distances() {
+        let mut camera = camera();
+        let pivot = vec2(0.5, -0.25);
+        let world_pivot = camera.screen_to_world_space(pivot);
+
+        camera.rotate_around(std::f32::consts::FRAC_PI_2, pivot);
+        camera.update(SETTLE_TIME);
+
+        assert_vec2_eq(camera.screen_to_world_space(pivot), world_pivot);
+
+        // A horizontal world vector ends up vertical with the same length in pixels (16:9, so x is scaled by 16/9).
+        let right = world_to_screen_space(&camera, world_pivot + vec2(0.1, 0.0)) - pivot;
+        assert_vec2_eq(right, vec2(0.0, 0.1 * 16.0 / 9.0));
+    }
+
+    #[test]
+    fn clamp_keeps_part_of_the_rotated_image_on_screen() {
+        let mut camera = camera();
+        camera.rotate_around(std::f32::consts::FRAC_PI_4, vec2(0.0, 0.0));
+        camera.translate(vec2(-5.0, 3.0));
+        camera.clamp_me_daddy();
+        camera.update(SETTLE_TIME);
+
+        let center = camera.screen_to_world_space(vec2(0.0, 0.0));
+
+        // The center is moved to the nearest point of the range, where the corner of the image just touches the screen.
+        assert_vec2_eq(
+            center.abs(),
+            vec2(1.0, 0.75) + camera.view_inner_half_extent(),
+        );
+
+        let image_corner = vec2(1.0, 0.75).component_mul(&center.map(f32::signum));
+        assert!(world_to_screen_space(&camera, image_corner).abs().max() <= 1.0 + 1e-4);
+    }
+
+    #[test]
+    fn clamp_keeps_the_view_inside_the_image() {
+        let mut camera = camera();
+        camera.set_clamp_policy(ClampPolicy::KeepViewInsideImage);
+        camera.zoom(4.0, vec2(0.8, 0.8));
+        camera.translate(vec2(-5.0, 5.0));
+        camera.clamp_me_daddy();
+        camera.update(SETTLE_TIME);
+
+        assert_vec2_eq(
+            camera.screen_to_world_space(vec2(1.0, -1.0)),
+            vec2(1.0, -0.75),
+        );
+
+        // Zoomed out too far to cover the screen, so the image is centered.
+        camera.zoom(0.1, vec2(0.8, 0.8));
+        camera.clamp_me_daddy();
+        camera.update(SETTLE_TIME);
+
+        assert_vec2_eq(camera.screen_to_world_space(vec2(0.0, 0.0)), vec2(0.0, 0.0));
+    }
+
+    #[test]
+    fn clamp_does_nothing_without_a_policy() {
+        let mut camera = camera();
+        camera.set_clamp_policy(ClampPolicy::None);
+        camera.translate(vec2(-5.0, 3.0));
+        camera.clamp_me_daddy();
+        camera.update(SETTLE_TIME);
+
+        assert_vec2_eq(
+            camera.screen_to_world_space(vec2(0.0, 0.0)),
+            vec2(5.0, -3.0),
+        );
+    }
+
+    #[test]
+    fn dragging_out_of_range_stretches_a_rubber_band() {
+        let mut camera = camera();
+        camera.set_clamp_policy(ClampPolicy::KeepViewInsideImage);
+        camera.zoom(2.0, vec2(0.0, 0.0));
+        camera.update(SETTLE_TIME);
+
+        // The view is 1 by 1 in world space, so the center can be at most 0.5 from the middle, and the rubber band
+        // stretches at most another 0.5.
+        camera.drag(vec2(-1.0, 0.0));
+        camera.update(SETTLE_TIME);
+        assert_vec2_eq(camera.screen_to_world_space(vec2(0.0, 0.0)), vec2(0.5, 0.0));
+
+        let mut overshoots = vec![0.0];
+        for _ in 0..10 {
+            camera.drag(vec2(-0.5, 0.0));
+            camera.update(SETTLE_TIME);
+
+            overshoots.push(camera.screen_to_world_space(vec2(0.0, 0.0)).x - 0.5);
+        }
+
+        for pair in overshoots.windows(2) {
+            // Moves less with every step, without ever reaching the limit.
+            assert!(pair[1] > pair[0] && pair[1] - pair[0] < 0.25);
+            assert!(pair[1] < 0.5);
+        }
+
+        // Dragging back the same way undoes it exactly.
+        camera.drag(vec2(6.0, 0.0));
+        camera.update(SETTLE_TIME);
+        assert_vec2_eq(camera.screen_to_world_space(vec2(0.0, 0.0)), vec2(0.0, 0.0));
+
+        // And letting go springs back to the edge.
+        camera.drag(vec2(-2.0, 0.0));
+        camera.clamp_me_daddy();
+        camera.update(SETTLE_TIME);
+        assert_vec2_eq(camera.screen_to_world_space(vec2(0.0, 0.0)), vec2(0.5, 0.0));
+    }
+
+    #[test]
+    fn zooming_mid_translation_finishes_the_translation() {
+        let mut expected = camera();
+        let mut camera = camera();
+        camera.translate(vec2(0.5, 0.0));
+        camera.update(0.05);
+        camera.zoom(2.0, vec2(-0.3, 0.4));
+        camera.update(SETTLE_TIME);
+
+        // Ends up the same as when translating first and zooming afterwards.
+        expected.translate(vec2(0.5, 0.0));
+        expected.update(SETTLE_TIME);
+        expected.zoom(2.0, vec2(-0.3, 0.4));
+        expected.update(SETTLE_TIME);
+
+        assert!((camera.zoom_factor() - 2.0).abs() < 1e-4);
+        for screen_point in [vec2(0.0, 0.0), vec2(-0.3, 0.4), vec2(1.0, -1.0)] {
+            assert_vec2_eq(
+                camera.screen_to_world_space(screen_point),
+                expected.screen_to_world_space(screen_point),
+            );
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    enum Operation {
+        Zoom(f32, Vec2),
+        Rotate(f32, Vec2),
+        Translate(Vec2),
+    }
+
+    fn screen_point() -> impl Strategy<Value = Vec2> {
+        (-1.0_f32..=1.0, -1.0_f32..=1.0).prop_map(|(x, y)| vec2(x, y))
+    }
+
+    fn operation() -> impl Strategy<Value = Operation> {
+        prop_oneof![
+            (0.2_f32..5.0, screen_point())
+                .prop_map(|(multiplier, point)| Operation::Zoom(multiplier, point)),
+            (-1.0_f32..1.0, screen_point())
+                .prop_map(|(angle, point)| Operation::Rotate(angle, point)),
+            screen_point().prop_map(Operation::Translate),
+        ]
+    }
+
+    proptest! {
+        /// Whatever was under the cursor when zooming or rotating stays there on every frame of the animation, even if
+        /// the previous one hasn't finished yet.
+        #[test]
+        fn zoom_and_rotation_keep_anchor_stationary(
+            steps in prop::collection::vec((operation(), prop::collection::vec(0.0_f32..0.1, 0..10)), 1..20)
+        ) {
+            let mut camera = camera();
+            for (operation, frame_times) in steps {
+                let anchor = match operation {
+                    Operation::Zoom(multiplier, point) => {
+                        let world_point = camera.screen_to_world_space(point);
+                        camera.zoom(multiplier, point);
+
+                        Some((point, world_point))
+                    }
+                    Operation::Rotate(angle, point) => {
+                        let world_point = camera.screen_to_world_space(point);
+                        camera.rotate_around(angle, point);
+
+                        Some((point, world_point))
+                    }
+                    Operation::Translate(translation) => {
+                        // Translations are meant to move the anchor, so let them finish before the next operation.
+                        camera.translate(translation);
+                        camera.update(SETTLE_TIME);
+
+                        None
+                    }
+                };
+
+                for dt in frame_times {
+                    camera.update(dt);
+
+                    if let Some((screen_point, world_point)) = anchor {
+                        let error = world_to_screen_space(&camera, world_point) - screen_point;
+                        prop_assert!(error.abs().max() < 1e-3, "anchor drifted by {}", error);
+                    }
+
+                    prop_assert!(camera.zoom_factor() >= 0.25 - 1e-4 && camera.zoom_factor() <= 500.0 + 1e-4);
+                }
+            }
+        }
+
+        /// Once settled, a translation moves everything on the screen by exactly that much, no matter how the camera
+        /// was zoomed, rotated or translated before, and whether that had finished.
+        #[test]
+        fn translation_moves_the_screen_exactly(
+            steps in prop::collection::vec((operation(), 0.0_f32..0.1), 0..20),
+            translation in screen_point(),
+            world_point in screen_point(),
+        ) {
+            let mut camera = camera();
+
+            for (operation, dt) in steps {
+                match operation {
+                    Operation::Zoom(multiplier, point) => camera.zoom(multiplier, point),
+                    Operation::Rotate(angle, point) => camera.rotate_around(angle, point),
+                    Operation::Translate(translation) => camera.translate(translation),
+                }
+
+                camera.update(dt);
+            }
+
+            camera.update(SETTLE_TIME);
+            let before = world_to_screen_space(&camera, world_point);
+
+            camera.translate(translation);
+            camera.update(SETTLE_TIME);
+            let after = world_to_screen_space(&camera, world_point);
+
+            let error = after - before - translation;
+            prop_assert!(error.abs().max() < 1e-3 * camera.zoom_factor().max(1.0), "off by {}", error);
+        }
+    }
+
+    #[test]
+    fn undo_and_redo_go_through_the_recorded_views() {
+        let mut camera = camera();
+        let initial = camera.view();
+
+        camera.zoom(4.0, vec2(0.5, 0.5));
+        camera.update(SETTLE_TIME);
+        camera.record_view();
+        let zoomed = camera.view();
+
+        // Not recorded while still moving.
+        camera.rotate_around(1.0, vec2(0.0, 0.0));
+        camera.update(0.01);
+        camera.record_view();
+        camera.update(SETTLE_TIME);
+        camera.record_view();
+
+        camera.undo();
+        camera.update(SETTLE_TIME);
+        assert!(views_are_close(&camera.view(), &zoomed));
+        assert_vec2_eq(camera.screen_to_world_space(vec2(0.0, 0.0)), zoomed.center);
+
+        camera.undo();
+        camera.update(SETTLE_TIME);
+        camera.undo();
+        camera.update(SETTLE_TIME);
+        assert!(views_are_close(&camera.view(), &initial));
+        assert_eq!(camera.zoom_factor(), 1.0);
+
+        camera.redo();
+        camera.update(SETTLE_TIME);
+        camera.record_view();
+        assert!(views_are_close(&camera.view(), &zoomed));
+
+        // Settling somewhere new drops the rotation that could have been redone.
+        camera.translate(vec2(0.1, 0.0));
+        camera.update(SETTLE_TIME);
+        camera.record_view();
+        let translated = camera.view();
+        camera.redo();
+        camera.update(SETTLE_TIME);
+        assert!(views_are_close(&camera.view(), &translated));
+    }
+
+    #[test]
+    fn undo_goes_back_to_the_last_recorded_view_first_and_is_bounded() {
+        let mut camera = camera();
+
+        for _ in 0..HISTORY_LENGTH + 10 {
+            camera.zoom(1.1, vec2(0.0, 0.0));
+            camera.update(SETTLE_TIME);
+            camera.record_view();
+        }
+        let last = camera.view();
+
+        // Cut short halfway through a zoom.
+        camera.zoom(2.0, vec2(0.0, 0.0));
+        camera.update(0.1);
+        camera.undo();
+        camera.update(SETTLE_TIME);
+        assert!(views_are_close(&camera.view(), &last));
+
+        for _ in 0..HISTORY_LENGTH + 10 {
+            camera.undo();
+            camera.update(SETTLE_TIME);
+        }
+        assert!((camera.zoom_factor() - 1.1f32.powi(11)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn flies_to_a_saved_view() {
+        let mut camera = camera();
+        camera.zoom(4.0, vec2(0.5, 0.5));
+        camera.rotate_around(0.3, vec2(-0.2, 0.1));
+        let saved = camera.view();
+
+        camera.translate(vec2(-0.7, 0.2));
+        camera.rotate_around(-4.0 * TAU - 0.2, vec2(0.0, 0.0));
+        camera.zoom(0.1, vec2(0.0, 0.0));
+        camera.update(SETTLE_TIME);
+
+        camera.fly_to_view(saved, 1.0);
+        camera.update(SETTLE_TIME);
+
+        let view = camera.view();
+        assert_vec2_eq(view.center, saved.center);
+        assert_vec2_eq(camera.screen_to_world_space(vec2(0.0, 0.0)), saved.center);
+        assert!((view.zoom_factor - saved.zoom_factor).abs() < 1e-3);
+        // Turned by 0.2 radians rather than four more times around.
+        assert!((camera.rotation() - (saved.rotation - 4.0 * TAU)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn flight_keeps_both_ends_in_view_and_can_be_cancelled() {
+        let mut camera = camera();
+        camera.zoom(50.0, vec2(0.0, 0.0));
+        camera.update(SETTLE_TIME);
+        let start = camera.screen_to_world_space(vec2(0.0, 0.0));
+        let end = vec2(0.8, -0.6);
+
+        camera.fly_to(end, 100.0, 2.0);
+        camera.update(1.0);
+
+        // Halfway through, both ends are on the screen.
+        for world_point in [start, end] {
+            let screen_point = world_to_screen_space(&camera, world_point);
+            assert!(screen_point.abs().max() <= 1.0, "{}", screen_point);
+        }
+
+        // Zooming stops the flight where it is.
+        let zoom_factor = camera.zoom_factor();
+        camera.zoom(2.0, vec2(0.0, 0.0));
+        camera.update(SETTLE_TIME);
+        assert!((camera.zoom_factor() - zoom_factor * 2.0).abs() < 1e-3);
+
+        camera.fly_to(end, 100.0, 2.0);
+        camera.update(SETTLE_TIME);
+        assert_vec2_eq(camera.screen_to_world_space(vec2(0.0, 0.0)), end);
+        assert!((camera.zoom_factor() - 100.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn zoom_to_rect_fills_the_screen() {
+        let mut camera = camera();
+        camera.rotate_around(0.4, vec2(0.3, 0.3));
+        camera.update(SETTLE_TIME);
+
+        let (corner, opposite_corner) = (vec2(0.1, -0.5), vec2(0.6, -0.3));
+        let world_center = camera.screen_to_world_space((corner + opposite_corner) / 2.0);
+        let world_corner = camera.screen_to_world_space(corner);
+
+        camera.zoom_to_rect(corner, opposite_corner);
+        camera.update(SETTLE_TIME);
+
+        // The rectangle is wider than the screen's aspect ratio, so its width fills the screen.
+        assert!((camera.zoom_factor() - 4.0).abs() < 1e-3);
+        assert_vec2_eq(world_to_screen_space(&camera, world_center), vec2(0.0, 0.0));
+        assert_vec2_eq(
+            world_to_screen_space(&camera, world_corner),
+            vec2(-1.0, -0.4),
+        );
+    }
+
+    #[test]
+    fn zoom_to_rect_centers_the_rect_at_the_zoom_limit() {
+        let mut camera = camera();
+        camera.zoom(400.0, vec2(0.0, 0.0));
+        camera.update(SETTLE_TIME);
+
+        let (corner, opposite_corner) = (vec2(0.5, 0.5), vec2(0.52, 0.51));
+        let world_center = camera.screen_to_world_space((corner + opposite_corner) / 2.0);
+
+        camera.zoom_to_rect(corner, opposite_corner);
+        camera.update(SETTLE_TIME);
+
+        assert!((camera.zoom_factor() - 500.0).abs() < 1e-2);
+        assert_vec2_eq(world_to_screen_space(&camera, world_center), vec2(0.0, 0.0));
+
+        // Already at the limit, so only centers the rectangle.
+        let world_corner = camera.screen_to_world_space(corner);
+
+        camera.zoom_to_rect(corner, vec2(0.7, 0.6));
+        camera.update(SETTLE_TIME);
+
+        assert!((camera.zoom_factor() - 500.0).abs() < 1e-2);
+        assert_vec2_eq(
+            world_to_screen_space(&camera, world_corner),
+            vec2(-0.1, -0.05),
+        );
+    }
+
+    #[test]
+    fn flung_camera_coasts_to_a_stop() {
+        let mut camera = camera();
+        let world_point = camera.screen_to_world_space(vec2(0.1, 0.1));
+
+        camera.fling(vec2(0.5, 0.0));
+        camera.update(1.0 / 60.0);
+        camera.update(SETTLE_TIME);
+
+        // Covers velocity / friction in total.
+        assert_vec2_eq(world_to_screen_space(&camera, world_point), vec2(0.2, 0.1));
+    }
+
+    #[test]
+    fn flung_camera_settles_inside_range() {
+        let mut camera = camera();
+        camera.zoom(2.0, vec2(0.0, 0.0));
+        camera.fling(vec2(-50.0, 0.0));
+
+        for _ in 0..120 {
+            camera.update(1.0 / 60.0);
+        }
+        camera.update(SETTLE_TIME);
+
+        // The edge of the image ends up at the edge of the screen.
+        let center = camera.screen_to_world_space(vec2(0.0, 0.0));
+        assert_vec2_eq(center, vec2(1.5, 0.0));
+    }
 }
@@ -1,5 +1,8 @@
-use std::{convert::TryInto, ffi::CString, num::ParseIntError};
+use std::num::ParseIntError;
+#[cfg(windows)]
+use std::{convert::TryInto, ffi::CString};
 
+#[cfg(windows)]
 use winapi::{
     ctypes::c_void,
     shared::{minwindef::TRUE, ntdef::HANDLE},
@@ -10,6 +13,7 @@ use winapi::{
     },
 };
 
+#[cfg(windows)]
 const STD_OUTPUT_HANDLE: u32 = -11_i32 as u32;
 
 fn escape_sequence(sequence: &str) -> String {
@@ -83,12 +87,15 @@ impl From<SimpleColor> for String {
     }
 }
 
+#[cfg(windows)]
 struct Console {
     std_out_handle: HANDLE,
 }
 
+#[cfg(windows)]
 static mut CONSOLE: Option<Console> = None;
 
+#[cfg(windows)]
 pub fn init() {
     unsafe {
         let std_out_handle = GetStdHandle(STD_OUTPUT_HANDLE);
@@ -112,6 +119,11 @@ pub fn init() {
     }
 }
 
+/// Terminals on other platforms understand ANSI sequences out of the box.
+#[cfg(not(windows))]
+pub fn init() {}
+
+#[cfg(windows)]
 pub fn write(message: impl Into<String>) -> u32 {
     let mut chars_written = 0;
     let message_string = message.into();
@@ -131,6 +143,18 @@ pub fn write(message: impl Into<String>) -> u32 {
     chars_written
 }
 
+#[cfg(not(windows))]
+pub fn write(message: impl Into<String>) -> u32 {
+    use std::io::Write;
+
+    let message_string = message.into();
+
+    print!("{}", message_string);
+    std::io::stdout().flush().unwrap();
+
+    message_string.len() as u32
+}
+
 pub fn writeln(message: impl Into<String>) -> u32 {
     write(format!("{}\r\n", message.into()))
 }
@@ -0,0 +1,176 @@
+use nalgebra_glm::{lerp, Vec2};
+
+/// Trade-off between zooming and panning, the value the paper found to look the most natural.
+const RHO: f64 = std::f64::consts::SQRT_2;
+
+/// A camera flight along the optimal zoom and pan path from "Smooth and efficient zooming and panning" by van Wijk and
+/// Nuij (<https://www.win.tue.nl/~vanwijk/zoompan.pdf>). Long distances are covered by zooming out first, panning at a
+/// magnification where both ends are close together, and zooming back in.
+///
+/// Centers and widths have to be in the same units, with both axes having the same scale.
+pub struct Flight {
+    start_center: Vec2,
+    end_center: Vec2,
+    start_width: f64,
+    end_width: f64,
+    path: Path,
+    duration: f32,
+    elapsed: f32,
+}
+
+enum Path {
+    /// Both ends are (almost) at the same center, so there is nothing to pan.
+    Zoom,
+    /// Zooms out and back in while panning.
+    ZoomAndPan {
+        distance: f64,
+        /// `r(0)` from the paper, where the path starts on its hyperbolic curve.
+        start: f64,
+        /// The length of the path, `S` from the paper.
+        length: f64,
+    },
+}
+
+impl Flight {
+    pub fn new(
+        start_center: Vec2,
+        start_width: f32,
+        end_center: Vec2,
+        end_width: f32,
+        duration: f32,
+    ) -> Self {
+        let (w0, w1) = (start_width as f64, end_width as f64);
+        let distance = (end_center - start_center).norm() as f64;
+
+        let path = if distance < 1e-6 * w0.max(w1) {
+            Path::Zoom
+        } else {
+            let rho2 = RHO * RHO;
+            let b = |width: f64, sign: f64| {
+                (w1 * w1 - w0 * w0 + sign * rho2 * rho2 * distance * distance)
+                    / (2.0 * width * rho2 * distance)
+            };
+            let r = |b: f64| (-b + (b * b + 1.0).sqrt()).ln();
+
+            let start = r(b(w0, 1.0));
+            let end = r(b(w1, -1.0));
+
+            Path::ZoomAndPan {
+                distance,
+                start,
+                length: (end - start) / RHO,
+            }
+        };
+
+        Self {
+            start_center,
+            end_center,
+            start_width: w0,
+            end_width: w1,
+            path,
+            duration,
+            elapsed: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.elapsed = (self.elapsed + dt).min(self.duration);
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    /// Returns the current center and width.
+    pub fn current(&self) -> (Vec2, f32) {
+        if self.is_finished() {
+            return (self.end_center, self.end_width as f32);
+        }
+
+        // Moves along the path at a constant speed, which the paper found to look best.
+        let t = (self.elapsed / self.duration) as f64;
+        let w0 = self.start_width;
+
+        match self.path {
+            Path::Zoom => (
+                lerp(&self.start_center, &self.end_center, t as f32),
+                (w0 * (self.end_width / w0).powf(t)) as f32,
+            ),
+            Path::ZoomAndPan {
+                distance,
+                start,
+                length,
+            } => {
+                let r = RHO * length * t + start;
+                let travelled = w0 / (RHO * RHO) * (start.cosh() * r.tanh() - start.sinh());
+                let width = w0 * start.cosh() / r.cosh();
+
+                (
+                    lerp(
+                        &self.start_center,
+                        &self.end_center,
+                        (travelled / distance) as f32,
+                    ),
+                    width as f32,
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra_glm::vec2;
+
+    use super::*;
+
+    fn flight_states(flight: &mut Flight, steps: usize) -> Vec<(Vec2, f32)> {
+        let dt = flight.duration / steps as f32;
+        let mut states = vec![flight.current()];
+
+        for _ in 0..steps {
+            flight.update(dt);
+            states.push(flight.current());
+        }
+
+        states
+    }
+
+    #[test]
+    fn zooms_out_to_cover_long_distances() {
+        let mut flight = Flight::new(vec2(-1.0, 0.0), 0.01, vec2(1.0, 0.5), 0.02, 2.0);
+        let states = flight_states(&mut flight, 100);
+
+        let (start_center, start_width) = states[0];
+        assert!((start_center - vec2(-1.0, 0.0)).norm() < 1e-5);
+        assert!((start_width - 0.01).abs() < 1e-6);
+        let (end_center, end_width) = *states.last().unwrap();
+        assert!((end_center - vec2(1.0, 0.5)).norm() < 1e-4);
+        assert!((end_width - 0.02).abs() < 1e-5);
+
+        flight.update(1.0);
+        assert!(flight.is_finished());
+        assert_eq!(flight.current(), (vec2(1.0, 0.5), 0.02));
+
+        // Both ends fit on the screen at the widest point of the path.
+        let widest = states.iter().map(|&(_, width)| width).fold(0.0, f32::max);
+        assert!(widest > 1.0, "{}", widest);
+
+        // Moves steadily along the straight line between the ends.
+        for pair in states.windows(2) {
+            let (a, b) = (pair[0].0, pair[1].0);
+            assert!((b - a).dot(&vec2(2.0, 0.5)) >= 0.0);
+        }
+    }
+
+    #[test]
+    fn zooms_in_place_without_panning() {
+        let mut flight = Flight::new(vec2(0.3, 0.3), 1.0, vec2(0.3, 0.3), 16.0, 1.0);
+
+        flight.update(0.5);
+
+        let (center, width) = flight.current();
+        assert_eq!(center, vec2(0.3, 0.3));
+        assert!((width - 4.0).abs() < 1e-4, "{}", width);
+    }
+}
@@ -4,8 +4,13 @@ use crate::ffi::c_str_ptr;
 use std::ffi::c_void;
 use std::mem::transmute;
 use std::os::raw::c_char;
+#[cfg(windows)]
 use winapi::shared::windef::{HDC, HGLRC};
-use winapi::um::wingdi::wglGetProcAddress;
+#[cfg(target_os = "linux")]
+use x11::{glx, xlib};
+
+#[cfg(all(any(test, feature = "headless"), target_os = "linux"))]
+use crate::platform::headless::egl;
 
 pub type GLuint = u32;
 pub type GLsizei = u32;
@@ -78,7 +83,10 @@ pub const GL_CLAMP_TO_EDGE: GLint = 0x812F;
 // glActiveTexture
 pub const GL_TEXTURE0: GLenum = 0x84C0;
 
-// wglCreateContextAttribsARB
+// glPixelStorei
+pub const GL_PACK_ALIGNMENT: GLenum = 0x0D05;
+
+// wglCreateContextAttribsARB, glXCreateContextAttribsARB
 pub const WGL_CONTEXT_MAJOR_VERSION_ARB: i32 = 0x2091;
 pub const WGL_CONTEXT_MINOR_VERSION_ARB: i32 = 0x2092;
 
@@ -130,6 +138,7 @@ pub fn debug_type_to_str(type_: GLenum) -> &'static str {
 }
 
 // OpenGL 1.0 and 1.1 methods should be linked statically. No one knows why.
+#[cfg_attr(target_os = "linux", link(name = "GL"))]
 extern "C" {
     // OpenGL 1.0
     pub fn glEnable(cap: GLenum);
@@ -173,6 +182,37 @@ extern "C" {
 
     // OpenGL 1.0
     pub fn glBlendFunc(sfactor: GLenum, dfactor: GLenum);
+
+    // OpenGL 1.0
+    pub fn glPixelStorei(pname: GLenum, param: GLint);
+    // OpenGL 1.0
+    pub fn glReadPixels(
+        x: GLint,
+        y: GLint,
+        width: GLsizei,
+        height: GLsizei,
+        format: GLenum,
+        type_: GLenum,
+        pixels: *mut GLvoid,
+    );
+}
+
+/// Looks up the address of an OpenGL or window system extension function, returning null if it doesn't exist.
+#[cfg(windows)]
+unsafe fn get_proc_address(name: *const c_char) -> *const c_void {
+    winapi::um::wingdi::wglGetProcAddress(name).cast()
+}
+
+/// Looks up the address of an OpenGL or window system extension function, returning null if it doesn't exist.
+#[cfg(target_os = "linux")]
+unsafe fn get_proc_address(name: *const c_char) -> *const c_void {
+    // Headless rendering goes through an EGL context, which has its own loader.
+    #[cfg(any(test, feature = "headless"))]
+    if !egl::eglGetCurrentContext().is_null() {
+        return egl::eglGetProcAddress(name);
+    }
+
+    glx::glXGetProcAddressARB(name.cast()).map_or(std::ptr::null(), |func| func as *const c_void)
 }
 
 // This should only be used for OpenGL 1.2 and greater functions. Again, no one knows why.
@@ -189,8 +229,8 @@ macro_rules! declare_opengl_function {
             static mut FPTR: Option<extern "C" fn ($($arg: $arg_ty),*) -> $return_type> = None;
 
             INIT.call_once(|| {
-                let func = wglGetProcAddress(c_str_ptr!(stringify!($name)));
-                assert!(!func.is_null(), "unable to load OpenGL/WGL function `{}`", stringify!($name));
+                let func = get_proc_address(c_str_ptr!(stringify!($name)));
+                assert!(!func.is_null(), "unable to load OpenGL/WGL/GLX function `{}`", stringify!($name));
                 FPTR = transmute::<_, _>(func)
             });
 
@@ -269,13 +309,34 @@ type DEBUGPROC = unsafe extern "C" fn(
 declare_opengl_function!(fn glDebugMessageCallback(callback: DEBUGPROC, userParam: *mut c_void));
 
 // https://www.khronos.org/registry/OpenGL/extensions/ARB/WGL_ARB_create_context.txt
+#[cfg(windows)]
 declare_opengl_function!(
     fn wglCreateContextAttribsARB(hdc: HDC, shareContext: HGLRC, attribList: *const i32) -> HGLRC
 );
 // https://www.khronos.org/registry/OpenGL/extensions/ARB/WGL_ARB_extensions_string.txt
+#[cfg(windows)]
 declare_opengl_function!(
     fn wglGetExtensionsStringARB(hdc: HDC) -> *const GLchar,
 );
 // https://www.khronos.org/registry/OpenGL/extensions/EXT/WGL_EXT_swap_control.txt
+#[cfg(windows)]
 declare_opengl_function!(fn wglSwapIntervalEXT(interval: i32) -> i32);
+#[cfg(windows)]
 declare_opengl_function!(fn wglGetSwapIntervalEXT() -> i32);
+
+// https://www.khronos.org/registry/OpenGL/extensions/ARB/GLX_ARB_create_context.txt
+#[cfg(target_os = "linux")]
+declare_opengl_function!(
+    fn glXCreateContextAttribsARB(
+        display: *mut xlib::Display,
+        config: glx::GLXFBConfig,
+        shareContext: glx::GLXContext,
+        direct: xlib::Bool,
+        attribList: *const i32,
+    ) -> glx::GLXContext
+);
+// https://www.khronos.org/registry/OpenGL/extensions/EXT/EXT_swap_control.txt
+#[cfg(target_os = "linux")]
+declare_opengl_function!(
+    fn glXSwapIntervalEXT(display: *mut xlib::Display, drawable: glx::GLXDrawable, interval: i32)
+);
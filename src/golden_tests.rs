@@ -0,0 +1,360 @@
+//! Renders a known capture through the real shaders and compares the frames against the reference images in
+//! `tests/golden`. After an intentional rendering change, run the tests with `ZOOMER_BLESS=1` to regenerate them.
+
+use std::{env, fs::File, io::BufWriter, path::Path};
+
+use nalgebra_glm::{vec2, Vec2};
+
+use crate::input::{InputEvent, Key, Modifiers, MouseButton, MouseButtons};
+use crate::monitors::Monitor;
+use crate::platform::headless::HeadlessPlatform;
+use crate::zoomer::{ZoomMode, Zoomer};
+
+const GOLDEN_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden");
+/// Frames that don't match their reference are written here for inspection.
+const FAILURE_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/target/golden");
+
+/// Maximum difference of a single channel for two pixels to be considered equal.
+const CHANNEL_TOLERANCE: i32 = 2;
+/// Different OpenGL implementations may round texel and highlighter edges differently, so allow a small fraction of
+/// the pixels to differ.
+const MAX_MISMATCHED_PIXEL_RATIO: f32 = 0.002;
+
+/// Long enough for every interpolation to settle.
+const SETTLE_TIME: f32 = 10.0;
+
+/// Gradients make flips and offsets visible, the 8x8 checkerboard makes scaling visible.
+fn test_pattern(x: i32, y: i32) -> [u8; 4] {
+    let checker = (x.div_euclid(8) + y.div_euclid(8)).rem_euclid(2) as u8;
+
+    [
+        x.rem_euclid(256) as u8,
+        y.rem_euclid(256) as u8,
+        checker * 0xFF,
+        0xFF,
+    ]
+}
+
+fn zoomer(client_size: (u32, u32), desktop_size: (u32, u32)) -> Zoomer<HeadlessPlatform> {
+    zoomer_with_monitors(client_size, &[(0, 0, desktop_size.0, desktop_size.1)])
+}
+
+/// Creates a zoomer whose desktop consists of monitors with the given `(x, y, width, height)` rectangles.
+fn zoomer_with_monitors(
+    client_size: (u32, u32),
+    monitor_rects: &[(i32, i32, u32, u32)],
+) -> Zoomer<HeadlessPlatform> {
+    let mut platform = HeadlessPlatform::with_opengl(client_size.0, client_size.1);
+    platform.monitors = monitor_rects
+        .iter()
+        .map(|&(x, y, width, height)| Monitor {
+            x,
+            y,
+            width,
+            height,
+            ..platform.monitors[0].clone()
+        })
+        .collect();
+    platform.desktop_pixel = test_pattern;
+
+    // Render into the fixed size client area. The camera starts out fitting the capture's width, so a wider capture
+    // is letterboxed while a taller one is cropped to the rows around its middle, eg. rows 46 to 113 of a 90x160 one.
+    let mut zoomer = Zoomer::new(platform);
+    zoomer.set_zoom_mode(ZoomMode::Window);
+    zoomer.init();
+    zoomer.init_renderer();
+
+    zoomer
+}
+
+fn wheel(
+    zoomer: &mut Zoomer<HeadlessPlatform>,
+    position: Vec2,
+    notches: f32,
+    modifiers: Modifiers,
+) {
+    zoomer.handle_event(InputEvent::Wheel {
+        position,
+        delta: vec2(0.0, notches),
+        modifiers,
+    });
+}
+
+fn drag(zoomer: &mut Zoomer<HeadlessPlatform>, from: Vec2, to: Vec2) {
+    zoomer.handle_event(InputEvent::PointerDown {
+        position: from,
+        button: MouseButton::Left,
+    });
+    zoomer.handle_event(InputEvent::PointerMove {
+        position: to,
+        buttons: MouseButtons {
+            left: true,
+            ..Default::default()
+        },
+    });
+    zoomer.handle_event(InputEvent::PointerUp {
+        position: to,
+        button: MouseButton::Left,
+    });
+}
+
+fn enable_highlighter(zoomer: &mut Zoomer<HeadlessPlatform>, position: Vec2) {
+    zoomer.handle_event(InputEvent::KeyDown {
+        key: Key::Letter('C'),
+        modifiers: Modifiers::NONE,
+    });
+    zoomer.handle_event(InputEvent::PointerMove {
+        position,
+        buttons: MouseButtons::default(),
+    });
+}
+
+fn read_png(path: &Path) -> Result<(u32, u32, Vec<u8>), png::DecodingError> {
+    let decoder = png::Decoder::new(File::open(path)?);
+    let mut reader = decoder.read_info()?;
+
+    let mut pixel_bytes = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixel_bytes)?;
+
+    assert_eq!(
+        (info.color_type, info.bit_depth),
+        (png::ColorType::Rgba, png::BitDepth::Eight),
+        "reference images must be 8-bit RGBA"
+    );
+
+    Ok((info.width, info.height, pixel_bytes))
+}
+
+fn write_png(path: &Path, width: u32, height: u32, pixel_bytes: &[u8]) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+
+    let mut encoder = png::Encoder::new(BufWriter::new(File::create(path).unwrap()), width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(pixel_bytes).unwrap();
+}
+
+/// Settles the camera, renders a frame and compares it against the reference image `name`.
+fn assert_matches_golden(name: &str, mut zoomer: Zoomer<HeadlessPlatform>) {
+    zoomer.update(SETTLE_TIME);
+
+    let image = zoomer.render_to_image();
+    let (width, height) = (image.width(), image.height());
+    let pixel_bytes = image.pixel_bytes().to_vec();
+
+    let golden_path = Path::new(GOLDEN_DIR).join(format!("{}.png", name));
+
+    if env::var_os("ZOOMER_BLESS").is_some() {
+        write_png(&golden_path, width, height, &pixel_bytes);
+
+        return;
+    }
+
+    let (golden_width, golden_height, golden_pixel_bytes) = read_png(&golden_path)
+        .unwrap_or_else(|error| {
+            panic!(
+                "unable to read reference image `{}` ({}), run the tests with ZOOMER_BLESS=1 to create it",
+                golden_path.display(),
+                error
+            )
+        });
+
+    assert_eq!(
+        (width, height),
+        (golden_width, golden_height),
+        "frame size doesn't match reference image `{}`",
+        name
+    );
+
+    let mismatched_pixels = pixel_bytes
+        .chunks_exact(4)
+        .zip(golden_pixel_bytes.chunks_exact(4))
+        .filter(|(pixel, golden_pixel)| {
+            pixel
+                .iter()
+                .zip(golden_pixel.iter())
+                .any(|(&a, &b)| (a as i32 - b as i32).abs() > CHANNEL_TOLERANCE)
+        })
+        .count();
+
+    let mismatched_pixel_ratio = mismatched_pixels as f32 / (width * height) as f32;
+
+    if mismatched_pixel_ratio > MAX_MISMATCHED_PIXEL_RATIO {
+        let failure_path = Path::new(FAILURE_DIR).join(format!("{}.png", name));
+        write_png(&failure_path, width, height, &pixel_bytes);
+
+        panic!(
+            "{} pixels ({:.2}%) differ from reference image `{}`, the frame was written to `{}`",
+            mismatched_pixels,
+            mismatched_pixel_ratio * 100.0,
+            golden_path.display(),
+            failure_path.display()
+        );
+    }
+}
+
+#[test]
+fn identity() {
+    assert_matches_golden("identity", zoomer((160, 120), (160, 120)));
+}
+
+#[test]
+fn zoom_in_at_center() {
+    let mut zoomer = zoomer((160, 120), (160, 120));
+
+    wheel(&mut zoomer, vec2(80.0, 60.0), 5.0, Modifiers::NONE);
+
+    assert_matches_golden("zoom_in_at_center", zoomer);
+}
+
+#[test]
+fn zoom_in_at_corner() {
+    let mut zoomer = zoomer((160, 120), (160, 120));
+
+    wheel(&mut zoomer, vec2(20.0, 100.0), 10.0, Modifiers::NONE);
+
+    assert_matches_golden("zoom_in_at_corner", zoomer);
+}
+
+#[test]
+fn zoom_in_far() {
+    let mut zoomer = zoomer((160, 120), (160, 120));
+
+    for _ in 0..10 {
+        wheel(&mut zoomer, vec2(50.0, 40.0), 10.0, Modifiers::NONE);
+        zoomer.update(SETTLE_TIME);
+    }
+
+    assert_matches_golden("zoom_in_far", zoomer);
+}
+
+#[test]
+fn zoom_out() {
+    let mut zoomer = zoomer((160, 120), (160, 120));
+
+    wheel(&mut zoomer, vec2(80.0, 60.0), -5.0, Modifiers::NONE);
+
+    assert_matches_golden("zoom_out", zoomer);
+}
+
+#[test]
+fn pan() {
+    let mut zoomer = zoomer((160, 120), (160, 120));
+
+    drag(&mut zoomer, vec2(40.0, 30.0), vec2(70.0, 50.0));
+
+    assert_matches_golden("pan", zoomer);
+}
+
+#[test]
+fn zoom_and_pan() {
+    let mut zoomer = zoomer((160, 120), (160, 120));
+
+    wheel(&mut zoomer, vec2(120.0, 30.0), 10.0, Modifiers::NONE);
+    zoomer.update(SETTLE_TIME);
+    drag(&mut zoomer, vec2(100.0, 80.0), vec2(60.0, 70.0));
+
+    assert_matches_golden("zoom_and_pan", zoomer);
+}
+
+#[test]
+fn wide_capture() {
+    assert_matches_golden("wide_capture", zoomer((160, 120), (240, 90)));
+}
+
+#[test]
+fn tall_capture() {
+    assert_matches_golden("tall_capture", zoomer((160, 120), (90, 160)));
+}
+
+#[test]
+fn zoom_in_on_wide_capture() {
+    let mut zoomer = zoomer((160, 120), (240, 90));
+
+    wheel(&mut zoomer, vec2(30.0, 45.0), 10.0, Modifiers::NONE);
+
+    assert_matches_golden("zoom_in_on_wide_capture", zoomer);
+}
+
+#[test]
+fn highlighter() {
+    let mut zoomer = zoomer((160, 120), (160, 120));
+
+    enable_highlighter(&mut zoomer, vec2(50.0, 40.0));
+
+    assert_matches_golden("highlighter", zoomer);
+}
+
+#[test]
+fn small_highlighter() {
+    let mut zoomer = zoomer((160, 120), (160, 120));
+
+    enable_highlighter(&mut zoomer, vec2(120.0, 90.0));
+    wheel(
+        &mut zoomer,
+        vec2(120.0, 90.0),
+        -3.0,
+        Modifiers {
+            ctrl: true,
+            ..Default::default()
+        },
+    );
+
+    assert_matches_golden("small_highlighter", zoomer);
+}
+
+#[test]
+fn highlighter_on_zoomed_tall_capture() {
+    let mut zoomer = zoomer((160, 120), (90, 160));
+
+    wheel(&mut zoomer, vec2(80.0, 30.0), 10.0, Modifiers::NONE);
+    zoomer.update(SETTLE_TIME);
+    enable_highlighter(&mut zoomer, vec2(70.0, 40.0));
+
+    assert_matches_golden("highlighter_on_zoomed_tall_capture", zoomer);
+}
+
+#[test]
+fn l_shaped_monitor_layout() {
+    // A landscape monitor with a smaller one below its right half, the area left of the latter isn't on any monitor.
+    let zoomer = zoomer_with_monitors((160, 120), &[(-40, -60, 160, 90), (40, 30, 80, 60)]);
+
+    assert_matches_golden("l_shaped_monitor_layout", zoomer);
+}
+
+#[test]
+fn rotated() {
+    let mut zoomer = zoomer((160, 120), (160, 120));
+
+    wheel(
+        &mut zoomer,
+        vec2(100.0, 40.0),
+        6.0,
+        Modifiers {
+            shift: true,
+            ..Default::default()
+        },
+    );
+
+    assert_matches_golden("rotated", zoomer);
+}
+
+#[test]
+fn zoomed_and_rotated_wide_capture() {
+    let mut zoomer = zoomer((160, 120), (240, 90));
+
+    wheel(&mut zoomer, vec2(60.0, 60.0), 8.0, Modifiers::NONE);
+    wheel(
+        &mut zoomer,
+        vec2(60.0, 60.0),
+        -9.0,
+        Modifiers {
+            shift: true,
+            ..Default::default()
+        },
+    );
+
+    assert_matches_golden("zoomed_and_rotated_wide_capture", zoomer);
+}
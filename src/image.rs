@@ -0,0 +1,776 @@
+use std::{
+    fmt,
+    fs::{self, File},
+    io::{self, BufWriter},
+    path::Path,
+};
+
+use crate::screenshot::Screenshot;
+
+#[derive(Debug)]
+pub enum ImageError {
+    Io(io::Error),
+    Png(png::DecodingError),
+    PngEncoding(png::EncodingError),
+    /// The file isn't a PNG, BMP or PNM image.
+    UnknownFormat,
+    /// The image uses a feature of its format which isn't supported.
+    Unsupported(&'static str),
+    Malformed(&'static str),
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImageError::Io(error) => write!(f, "{}", error),
+            ImageError::Png(error) => write!(f, "invalid PNG: {}", error),
+            ImageError::PngEncoding(error) => write!(f, "unable to encode PNG: {}", error),
+            ImageError::UnknownFormat => write!(f, "not a PNG, BMP or PNM image"),
+            ImageError::Unsupported(what) => write!(f, "unsupported {}", what),
+            ImageError::Malformed(what) => write!(f, "malformed {}", what),
+        }
+    }
+}
+
+impl From<io::Error> for ImageError {
+    fn from(error: io::Error) -> Self {
+        ImageError::Io(error)
+    }
+}
+
+impl From<png::DecodingError> for ImageError {
+    fn from(error: png::DecodingError) -> Self {
+        ImageError::Png(error)
+    }
+}
+
+impl From<png::EncodingError> for ImageError {
+    fn from(error: png::EncodingError) -> Self {
+        ImageError::PngEncoding(error)
+    }
+}
+
+/// Loads a PNG, BMP or PNM (PBM, PGM, PPM) image into an RGBA [`Screenshot`]. The format is detected from the
+/// file's contents, not its extension.
+pub fn load(path: &Path) -> Result<Screenshot, ImageError> {
+    decode(&fs::read(path)?)
+}
+
+pub fn decode(bytes: &[u8]) -> Result<Screenshot, ImageError> {
+    match bytes {
+        [0x89, b'P', b'N', b'G', ..] => decode_png(bytes),
+        [b'B', b'M', ..] => decode_bmp(bytes),
+        [b'P', b'1'..=b'6', ..] => decode_pnm(bytes),
+        _ => Err(ImageError::UnknownFormat),
+    }
+}
+
+/// Saves `image` as an 8-bit RGBA PNG, replacing the file at `path` if there is one.
+pub fn save_png(path: &Path, image: &Screenshot) -> Result<(), ImageError> {
+    let mut encoder = png::Encoder::new(
+        BufWriter::new(File::create(path)?),
+        image.width(),
+        image.height(),
+    );
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder.write_header()?;
+
+    let row_size = (image.width() * Screenshot::BYTES_PER_PIXEL) as usize;
+
+    if image.stride() as usize == row_size {
+        writer.write_image_data(&image.pixel_bytes()[..row_size * image.height() as usize])?;
+    } else {
+        // Captures may pad their rows, PNG rows are tightly packed.
+        let pixel_bytes: Vec<u8> = image
+            .pixel_bytes()
+            .chunks(image.stride() as usize)
+            .take(image.height() as usize)
+            .flat_map(|row| &row[..row_size])
+            .copied()
+            .collect();
+
+        writer.write_image_data(&pixel_bytes)?;
+    }
+
+    writer.finish()?;
+
+    Ok(())
+}
+
+fn decode_png(bytes: &[u8]) -> Result<Screenshot, ImageError> {
+    let mut decoder = png::Decoder::new(bytes);
+    // Expand palettes and bit depths below 8 and strip 16-bit channels, so every channel ends up being a byte.
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+
+    let mut reader = decoder.read_info()?;
+
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer)?;
+    let pixels = &buffer[..info.buffer_size()];
+
+    let pixel_bytes = match info.color_type {
+        png::ColorType::Rgba => pixels.to_vec(),
+        png::ColorType::Rgb => pixels
+            .chunks_exact(3)
+            .flat_map(|pixel| [pixel[0], pixel[1], pixel[2], 0xFF])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => pixels
+            .chunks_exact(2)
+            .flat_map(|pixel| [pixel[0], pixel[0], pixel[0], pixel[1]])
+            .collect(),
+        png::ColorType::Grayscale => pixels
+            .iter()
+            .flat_map(|&gray| [gray, gray, gray, 0xFF])
+            .collect(),
+        png::ColorType::Indexed => unreachable!("palettes are expanded by the decoder"),
+    };
+
+    Ok(rgba_screenshot(info.width, info.height, pixel_bytes))
+}
+
+// Source: https://docs.microsoft.com/en-us/windows/win32/gdi/bitmap-storage
+const BI_RGB: u32 = 0;
+const BI_BITFIELDS: u32 = 3;
+const BI_ALPHABITFIELDS: u32 = 6;
+
+/// Size of `BITMAPFILEHEADER`, the info header follows it.
+const BMP_FILE_HEADER_SIZE: usize = 14;
+/// Size of `BITMAPCOREHEADER`, the OS/2 header with 16-bit dimensions and 3-byte palette entries.
+const BMP_CORE_HEADER_SIZE: usize = 12;
+/// Size of `BITMAPINFOHEADER`, with `BI_BITFIELDS` it is followed by the color masks.
+const BMP_INFO_HEADER_SIZE: usize = 40;
+
+/// Decodes an uncompressed BMP with 1, 4, 8, 16, 24 or 32 bits per pixel.
+fn decode_bmp(bytes: &[u8]) -> Result<Screenshot, ImageError> {
+    const MALFORMED: ImageError = ImageError::Malformed("BMP");
+
+    let read_u16 = |offset: usize| {
+        bytes
+            .get(offset..offset + 2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]))
+            .ok_or(MALFORMED)
+    };
+    let read_u32 = |offset: usize| {
+        bytes
+            .get(offset..offset + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .ok_or(MALFORMED)
+    };
+
+    let pixel_data_offset = read_u32(10)? as usize;
+    let header_size = read_u32(BMP_FILE_HEADER_SIZE)? as usize;
+
+    let (width, height, bits_per_pixel, compression, palette_size) =
+        if header_size == BMP_CORE_HEADER_SIZE {
+            (
+                read_u16(18)? as i16 as i32,
+                read_u16(20)? as i16 as i32,
+                read_u16(24)?,
+                BI_RGB,
+                0,
+            )
+        } else if header_size >= BMP_INFO_HEADER_SIZE {
+            (
+                read_u32(18)? as i32,
+                read_u32(22)? as i32,
+                read_u16(28)?,
+                read_u32(30)?,
+                read_u32(46)? as usize,
+            )
+        } else {
+            return Err(MALFORMED);
+        };
+
+    // A negative height means the rows are stored top-down instead of bottom-up.
+    let is_top_down = height < 0;
+    let (width, height) = (width.unsigned_abs(), height.unsigned_abs());
+
+    if width == 0 || height == 0 {
+        return Err(MALFORMED);
+    }
+
+    let pixel_count = checked_pixel_count(width, height).ok_or(MALFORMED)?;
+
+    let masks = match (compression, bits_per_pixel) {
+        (BI_RGB, 16) => [0x7C00, 0x03E0, 0x001F, 0],
+        (BI_RGB, 32) => [0x00FF_0000, 0x0000_FF00, 0x0000_00FF, 0],
+        (BI_BITFIELDS | BI_ALPHABITFIELDS, 16 | 32) => {
+            // The masks are either part of a V2+ header or directly follow a `BITMAPINFOHEADER`, in both cases
+            // they start right after the `BITMAPINFOHEADER` fields.
+            let masks_offset = BMP_FILE_HEADER_SIZE + BMP_INFO_HEADER_SIZE;
+            let has_alpha_mask = compression == BI_ALPHABITFIELDS || header_size >= 56;
+
+            [
+                read_u32(masks_offset)?,
+                read_u32(masks_offset + 4)?,
+                read_u32(masks_offset + 8)?,
+                if has_alpha_mask {
+                    read_u32(masks_offset + 12)?
+                } else {
+                    0
+                },
+            ]
+        }
+        (BI_RGB, 1 | 4 | 8 | 24) => [0; 4],
+        (BI_RGB | BI_BITFIELDS | BI_ALPHABITFIELDS, _) => {
+            return Err(ImageError::Unsupported("BMP bit depth"))
+        }
+        _ => return Err(ImageError::Unsupported("BMP compression")),
+    };
+
+    let palette = if bits_per_pixel <= 8 {
+        let palette_offset = BMP_FILE_HEADER_SIZE
+            .checked_add(header_size)
+            .ok_or(MALFORMED)?;
+        let entry_size = if header_size == BMP_CORE_HEADER_SIZE {
+            3
+        } else {
+            4
+        };
+        // A palette size of 0 means the palette has the maximum number of entries for the bit depth.
+        let entry_count = match palette_size {
+            0 => 1 << bits_per_pixel,
+            palette_size => palette_size.min(1 << bits_per_pixel),
+        };
+
+        let palette_end = palette_offset
+            .checked_add(entry_count * entry_size)
+            .ok_or(MALFORMED)?;
+
+        bytes
+            .get(palette_offset..palette_end)
+            .ok_or(MALFORMED)?
+            .chunks_exact(entry_size)
+            .map(|bgr| [bgr[2], bgr[1], bgr[0], 0xFF])
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    // Rows are padded to a multiple of 4 bytes.
+    let row_bits = (bits_per_pixel as usize)
+        .checked_mul(width as usize)
+        .and_then(|bits| bits.checked_add(31))
+        .ok_or(MALFORMED)?;
+    let row_size = row_bits / 32 * 4;
+    let pixel_data_end = row_size
+        .checked_mul(height as usize)
+        .and_then(|size| size.checked_add(pixel_data_offset))
+        .ok_or(MALFORMED)?;
+    let pixel_data = bytes
+        .get(pixel_data_offset..pixel_data_end)
+        .ok_or(MALFORMED)?;
+
+    let mut pixel_bytes = Vec::with_capacity(pixel_count * Screenshot::BYTES_PER_PIXEL as usize);
+
+    for y in 0..height as usize {
+        let row_index = if is_top_down {
+            y
+        } else {
+            height as usize - 1 - y
+        };
+        let row = &pixel_data[row_index * row_size..(row_index + 1) * row_size];
+
+        for x in 0..width as usize {
+            let pixel = match bits_per_pixel {
+                1 | 4 | 8 => {
+                    let bits = bits_per_pixel as usize;
+                    let pixels_per_byte = 8 / bits;
+                    // The leftmost pixel is in the most significant bits.
+                    let shift = 8 - bits * (x % pixels_per_byte + 1);
+                    let index = (row[x / pixels_per_byte] >> shift) as usize & ((1 << bits) - 1);
+
+                    *palette.get(index).ok_or(MALFORMED)?
+                }
+                24 => [row[x * 3 + 2], row[x * 3 + 1], row[x * 3], 0xFF],
+                _ => {
+                    let value = if bits_per_pixel == 16 {
+                        u16::from_le_bytes([row[x * 2], row[x * 2 + 1]]) as u32
+                    } else {
+                        u32::from_le_bytes([
+                            row[x * 4],
+                            row[x * 4 + 1],
+                            row[x * 4 + 2],
+                            row[x * 4 + 3],
+                        ])
+                    };
+
+                    [
+                        extract_masked_channel(value, masks[0]).unwrap_or(0),
+                        extract_masked_channel(value, masks[1]).unwrap_or(0),
+                        extract_masked_channel(value, masks[2]).unwrap_or(0),
+                        extract_masked_channel(value, masks[3]).unwrap_or(0xFF),
+                    ]
+                }
+            };
+
+            pixel_bytes.extend_from_slice(&pixel);
+        }
+    }
+
+    Ok(rgba_screenshot(width, height, pixel_bytes))
+}
+
+/// Extracts the channel selected by `mask` from `value` and scales it to 8 bits. Returns `None` if `mask` is empty.
+fn extract_masked_channel(value: u32, mask: u32) -> Option<u8> {
+    if mask == 0 {
+        return None;
+    }
+
+    let shift = mask.trailing_zeros();
+    let max = (mask >> shift) as u64;
+    let channel = ((value & mask) >> shift) as u64;
+
+    Some(((channel * 0xFF + max / 2) / max) as u8)
+}
+
+/// Decodes a plain (ASCII) or raw (binary) PBM, PGM or PPM image.
+fn decode_pnm(bytes: &[u8]) -> Result<Screenshot, ImageError> {
+    let mut reader = PnmReader { bytes, position: 2 };
+    let kind = bytes[1];
+
+    let width = reader.read_number()?;
+    let height = reader.read_number()?;
+    let max_value = if matches!(kind, b'1' | b'4') {
+        1
+    } else {
+        reader.read_number()?
+    };
+
+    if width == 0 || height == 0 || max_value == 0 || max_value > u16::MAX as u32 {
+        return Err(ImageError::Malformed("PNM header"));
+    }
+
+    let pixel_count =
+        checked_pixel_count(width, height).ok_or(ImageError::Malformed("PNM header"))?;
+    let scale = |value: u32| -> Result<u8, ImageError> {
+        if value > max_value {
+            return Err(ImageError::Malformed("PNM sample"));
+        }
+
+        Ok(((value * 0xFF + max_value / 2) / max_value) as u8)
+    };
+
+    let mut pixel_bytes = Vec::new();
+
+    match kind {
+        // In bitmaps 1 is black. Plain bitmaps don't need whitespace between the samples.
+        b'1' => {
+            for _ in 0..pixel_count {
+                let gray = if reader.read_bit()? { 0x00 } else { 0xFF };
+
+                pixel_bytes.extend_from_slice(&[gray, gray, gray, 0xFF]);
+            }
+        }
+        b'2' | b'3' => {
+            for _ in 0..pixel_count {
+                let pixel = if kind == b'2' {
+                    let gray = scale(reader.read_number()?)?;
+                    [gray, gray, gray, 0xFF]
+                } else {
+                    [
+                        scale(reader.read_number()?)?,
+                        scale(reader.read_number()?)?,
+                        scale(reader.read_number()?)?,
+                        0xFF,
+                    ]
+                };
+
+                pixel_bytes.extend_from_slice(&pixel);
+            }
+        }
+        // Raw bitmaps pack 8 pixels into a byte, with every row starting on a new byte.
+        b'4' => {
+            let row_size = (width as usize + 7) / 8;
+            let data = reader.raw_data(row_size.checked_mul(height as usize))?;
+
+            for row in data.chunks_exact(row_size) {
+                for x in 0..width as usize {
+                    let gray = if row[x / 8] & (0x80 >> (x % 8)) != 0 {
+                        0x00
+                    } else {
+                        0xFF
+                    };
+
+                    pixel_bytes.extend_from_slice(&[gray, gray, gray, 0xFF]);
+                }
+            }
+        }
+        _ => {
+            let channels = if kind == b'5' { 1 } else { 3 };
+            // Samples are 2 bytes big endian if the max value doesn't fit into a byte.
+            let sample_size = if max_value > 0xFF { 2 } else { 1 };
+            let data = reader.raw_data(pixel_count.checked_mul(channels * sample_size))?;
+
+            let samples = data.chunks_exact(sample_size).map(|sample| {
+                scale(if sample_size == 2 {
+                    u16::from_be_bytes([sample[0], sample[1]]) as u32
+                } else {
+                    sample[0] as u32
+                })
+            });
+
+            if channels == 1 {
+                for gray in samples {
+                    let gray = gray?;
+                    pixel_bytes.extend_from_slice(&[gray, gray, gray, 0xFF]);
+                }
+            } else {
+                let samples = samples.collect::<Result<Vec<_>, _>>()?;
+
+                for rgb in samples.chunks_exact(3) {
+                    pixel_bytes.extend_from_slice(&[rgb[0], rgb[1], rgb[2], 0xFF]);
+                }
+            }
+        }
+    }
+
+    Ok(rgba_screenshot(width, height, pixel_bytes))
+}
+
+struct PnmReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl PnmReader<'_> {
+    fn skip_whitespace_and_comments(&mut self) {
+        while let Some(&byte) = self.bytes.get(self.position) {
+            if byte == b'#' {
+                while matches!(self.bytes.get(self.position), Some(&byte) if byte != b'\n') {
+                    self.position += 1;
+                }
+            } else if !byte.is_ascii_whitespace() {
+                break;
+            }
+
+            self.position += 1;
+        }
+    }
+
+    fn read_number(&mut self) -> Result<u32, ImageError> {
+        self.skip_whitespace_and_comments();
+
+        let start = self.position;
+
+        while matches!(self.bytes.get(self.position), Some(byte) if byte.is_ascii_digit()) {
+            self.position += 1;
+        }
+
+        std::str::from_utf8(&self.bytes[start..self.position])
+            .unwrap()
+            .parse()
+            .map_err(|_| ImageError::Malformed("PNM number"))
+    }
+
+    fn read_bit(&mut self) -> Result<bool, ImageError> {
+        self.skip_whitespace_and_comments();
+
+        let bit = match self.bytes.get(self.position) {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return Err(ImageError::Malformed("PNM bitmap")),
+        };
+        self.position += 1;
+
+        Ok(bit)
+    }
+
+    /// Returns the `size` bytes of binary data following the header, which is terminated by a single whitespace.
+    /// `size` is `None` if computing it overflowed.
+    fn raw_data(&self, size: Option<usize>) -> Result<&[u8], ImageError> {
+        let start = self.position + 1;
+
+        size.and_then(|size| size.checked_add(start))
+            .and_then(|end| self.bytes.get(start..end))
+            .ok_or(ImageError::Malformed("PNM pixel data"))
+    }
+}
+
+/// Returns the number of pixels of an image, or `None` if it's too large for its RGBA pixel data to be addressed.
+fn checked_pixel_count(width: u32, height: u32) -> Option<usize> {
+    width.checked_mul(Screenshot::BYTES_PER_PIXEL)?;
+
+    let pixel_count = (width as usize).checked_mul(height as usize)?;
+    pixel_count.checked_mul(Screenshot::BYTES_PER_PIXEL as usize)?;
+
+    Some(pixel_count)
+}
+
+fn rgba_screenshot(width: u32, height: u32, pixel_bytes: Vec<u8>) -> Screenshot {
+    Screenshot::new(
+        width,
+        height,
+        pixel_bytes,
+        width * Screenshot::BYTES_PER_PIXEL,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode_pixels(bytes: &[u8]) -> (u32, u32, Vec<[u8; 4]>) {
+        let image = decode(bytes).unwrap();
+        let pixels = image
+            .pixel_bytes()
+            .chunks_exact(4)
+            .map(|pixel| [pixel[0], pixel[1], pixel[2], pixel[3]])
+            .collect();
+
+        (image.width(), image.height(), pixels)
+    }
+
+    /// Builds a BMP with a `BITMAPINFOHEADER` followed by `extra` (masks or palette) and the pixel data.
+    fn bmp(
+        width: i32,
+        height: i32,
+        bits_per_pixel: u16,
+        compression: u32,
+        extra: &[u8],
+        pixel_data: &[u8],
+    ) -> Vec<u8> {
+        let pixel_data_offset = (BMP_FILE_HEADER_SIZE + BMP_INFO_HEADER_SIZE + extra.len()) as u32;
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"BM");
+        bytes.extend_from_slice(&(pixel_data_offset + pixel_data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&[0; 4]);
+        bytes.extend_from_slice(&pixel_data_offset.to_le_bytes());
+
+        bytes.extend_from_slice(&(BMP_INFO_HEADER_SIZE as u32).to_le_bytes());
+        bytes.extend_from_slice(&width.to_le_bytes());
+        bytes.extend_from_slice(&height.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&bits_per_pixel.to_le_bytes());
+        bytes.extend_from_slice(&compression.to_le_bytes());
+        bytes.extend_from_slice(&[0; 12]);
+        // Palette size.
+        let palette_size = if bits_per_pixel <= 8 {
+            extra.len() / 4
+        } else {
+            0
+        };
+        bytes.extend_from_slice(&(palette_size as u32).to_le_bytes());
+        bytes.extend_from_slice(&[0; 4]);
+
+        bytes.extend_from_slice(extra);
+        bytes.extend_from_slice(pixel_data);
+
+        bytes
+    }
+
+    const RED: [u8; 4] = [0xFF, 0x00, 0x00, 0xFF];
+    const GREEN: [u8; 4] = [0x00, 0xFF, 0x00, 0xFF];
+    const BLUE: [u8; 4] = [0x00, 0x00, 0xFF, 0xFF];
+    const WHITE: [u8; 4] = [0xFF, 0xFF, 0xFF, 0xFF];
+    const BLACK: [u8; 4] = [0x00, 0x00, 0x00, 0xFF];
+
+    #[test]
+    fn png_rgb() {
+        let mut bytes = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut bytes, 2, 1);
+            encoder.set_color(png::ColorType::Rgb);
+            encoder.set_depth(png::BitDepth::Eight);
+
+            let mut writer = encoder.write_header().unwrap();
+            writer.write_image_data(&[0xFF, 0, 0, 0, 0, 0xFF]).unwrap();
+        }
+
+        assert_eq!(decode_pixels(&bytes), (2, 1, vec![RED, BLUE]));
+    }
+
+    #[test]
+    fn png_grayscale_alpha() {
+        let mut bytes = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut bytes, 1, 2);
+            encoder.set_color(png::ColorType::GrayscaleAlpha);
+            encoder.set_depth(png::BitDepth::Eight);
+
+            let mut writer = encoder.write_header().unwrap();
+            writer.write_image_data(&[0x80, 0xFF, 0x10, 0x40]).unwrap();
+        }
+
+        assert_eq!(
+            decode_pixels(&bytes),
+            (
+                1,
+                2,
+                vec![[0x80, 0x80, 0x80, 0xFF], [0x10, 0x10, 0x10, 0x40]]
+            )
+        );
+    }
+
+    #[test]
+    fn bmp_24_bit_bottom_up() {
+        // 3 pixels are 9 bytes, padded to 12. The bottom row comes first.
+        #[rustfmt::skip]
+        let pixel_data = [
+            0x00, 0x00, 0xFF,  0x00, 0xFF, 0x00,  0xFF, 0x00, 0x00,  0, 0, 0,
+            0xFF, 0xFF, 0xFF,  0x00, 0x00, 0x00,  0x00, 0x00, 0xFF,  0, 0, 0,
+        ];
+
+        assert_eq!(
+            decode_pixels(&bmp(3, 2, 24, BI_RGB, &[], &pixel_data)),
+            (3, 2, vec![WHITE, BLACK, RED, RED, GREEN, BLUE])
+        );
+    }
+
+    #[test]
+    fn bmp_32_bit_bitfields_top_down() {
+        // RGBA byte order, alpha included.
+        let masks = [0x0000_00FFu32, 0x0000_FF00, 0x00FF_0000, 0xFF00_0000]
+            .iter()
+            .flat_map(|mask| mask.to_le_bytes())
+            .collect::<Vec<_>>();
+        let pixel_data = [0xFF, 0x00, 0x00, 0x80, 0x00, 0x00, 0xFF, 0xFF];
+
+        assert_eq!(
+            decode_pixels(&bmp(1, -2, 32, BI_ALPHABITFIELDS, &masks, &pixel_data)),
+            (1, 2, vec![[0xFF, 0x00, 0x00, 0x80], BLUE])
+        );
+    }
+
+    #[test]
+    fn bmp_16_bit_555() {
+        let pixel_data = [0x00, 0x7C, 0x1F, 0x00];
+
+        assert_eq!(
+            decode_pixels(&bmp(2, 1, 16, BI_RGB, &[], &pixel_data)),
+            (2, 1, vec![RED, BLUE])
+        );
+    }
+
+    #[test]
+    fn bmp_4_bit_palette() {
+        #[rustfmt::skip]
+        let palette = [
+            0x00, 0x00, 0x00, 0x00,
+            0x00, 0xFF, 0x00, 0x00,
+            0xFF, 0x00, 0x00, 0x00,
+        ];
+        // Pixels 1, 2, 0, padded to 4 bytes.
+        let pixel_data = [0x12, 0x00, 0x00, 0x00];
+
+        assert_eq!(
+            decode_pixels(&bmp(3, 1, 4, BI_RGB, &palette, &pixel_data)),
+            (3, 1, vec![GREEN, BLUE, BLACK])
+        );
+    }
+
+    #[test]
+    fn bmp_rle_is_unsupported() {
+        let bytes = bmp(1, 1, 8, 1, &[0; 4], &[0; 4]);
+
+        assert!(matches!(decode(&bytes), Err(ImageError::Unsupported(_))));
+    }
+
+    #[test]
+    fn truncated_bmp_is_malformed() {
+        let bytes = bmp(3, 2, 24, BI_RGB, &[], &[0; 12]);
+
+        assert!(matches!(decode(&bytes), Err(ImageError::Malformed(_))));
+    }
+
+    #[test]
+    fn huge_bmp_is_malformed() {
+        for (width, height) in [
+            (i32::MAX, i32::MAX),
+            (i32::MAX, 1),
+            (0x1000_0000, -0x1000_0000),
+        ] {
+            let bytes = bmp(width, height, 32, BI_RGB, &[], &[0; 16]);
+
+            assert!(matches!(decode(&bytes), Err(ImageError::Malformed(_))));
+        }
+    }
+
+    #[test]
+    fn huge_pnm_is_malformed() {
+        for header in [
+            &b"P6 4294967295 4294967295 65535\n\0\0\0\0\0\0"[..],
+            b"P5 4294967295 1 255\n\0",
+            b"P4 4294967295 4294967295\n\0",
+            b"P3 4294967295 4294967295 255\n0 0 0",
+        ] {
+            assert!(matches!(decode(header), Err(ImageError::Malformed(_))));
+        }
+    }
+
+    #[test]
+    fn plain_ppm_with_comments() {
+        let bytes = b"P3\n# a comment\n2 1 # trailing comment\n15\n15 0 0   0 15 0\n";
+
+        assert_eq!(decode_pixels(bytes), (2, 1, vec![RED, GREEN]));
+    }
+
+    #[test]
+    fn raw_ppm() {
+        let mut bytes = b"P6 2 1 255\n".to_vec();
+        bytes.extend_from_slice(&[0x00, 0x00, 0xFF, 0x12, 0x34, 0x56]);
+
+        assert_eq!(
+            decode_pixels(&bytes),
+            (2, 1, vec![BLUE, [0x12, 0x34, 0x56, 0xFF]])
+        );
+    }
+
+    #[test]
+    fn raw_pgm_16_bit() {
+        let mut bytes = b"P5 2 1 65535\n".to_vec();
+        bytes.extend_from_slice(&[0xFF, 0xFF, 0x00, 0x00]);
+
+        assert_eq!(decode_pixels(&bytes), (2, 1, vec![WHITE, BLACK]));
+    }
+
+    #[test]
+    fn plain_pbm_without_whitespace() {
+        assert_eq!(
+            decode_pixels(b"P1 3 1\n010"),
+            (3, 1, vec![WHITE, BLACK, WHITE])
+        );
+    }
+
+    #[test]
+    fn raw_pbm() {
+        // 10 pixels per row take 2 bytes.
+        let mut bytes = b"P4 10 1\n".to_vec();
+        bytes.extend_from_slice(&[0b1000_0000, 0b0100_0000]);
+
+        let (_, _, pixels) = decode_pixels(&bytes);
+
+        assert_eq!(pixels[0], BLACK);
+        assert_eq!(pixels[1..9], [WHITE; 8]);
+        assert_eq!(pixels[9], BLACK);
+    }
+
+    #[test]
+    fn unknown_format() {
+        assert!(matches!(decode(b"GIF89a"), Err(ImageError::UnknownFormat)));
+    }
+
+    #[test]
+    fn saved_png_round_trips_padded_rows() {
+        let red = [0xFF, 0x00, 0x00, 0xFF];
+        let green = [0x00, 0xFF, 0x00, 0x80];
+
+        // Two pixels per row padded to 12 bytes, like a capture with an aligned stride.
+        let mut pixel_bytes = Vec::new();
+        for row in [[red, green], [green, red]] {
+            pixel_bytes.extend(row.iter().flatten());
+            pixel_bytes.extend([0xAA; 4]);
+        }
+
+        let path = std::env::temp_dir().join("zoomer_saved_png_round_trips_padded_rows.png");
+        save_png(&path, &Screenshot::new(2, 2, pixel_bytes, 12)).unwrap();
+
+        assert_eq!(
+            decode_pixels(&fs::read(&path).unwrap()),
+            (2, 2, vec![red, green, green, red])
+        );
+    }
+}
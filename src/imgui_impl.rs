@@ -1,13 +1,22 @@
-use std::{ffi::c_void, os::raw::c_char};
+use std::os::raw::c_char;
 
+#[cfg(windows)]
+use std::ffi::c_void;
+#[cfg(windows)]
 use winapi::shared::{
     minwindef::{LPARAM, LRESULT, UINT, WPARAM},
     windef::HWND,
 };
 
 extern "C" {
-    pub fn ImGui_ImplWin32_Init(window: *const c_void) -> bool;
     pub fn ImGui_ImplOpenGL3_Init(gl_version: *const c_char) -> bool;
+    pub fn ImGui_ImplOpenGL3_NewFrame();
+    pub fn ImGui_ImplOpenGL3_RenderDrawData(draw_data: *mut imgui::DrawData);
+}
+
+#[cfg(windows)]
+extern "C" {
+    pub fn ImGui_ImplWin32_Init(window: *const c_void) -> bool;
 
     pub fn ImGui_ImplWin32_WndProcHandler(
         window: HWND,
@@ -16,8 +25,5 @@ extern "C" {
         l_param: LPARAM,
     ) -> LRESULT;
 
-    pub fn ImGui_ImplOpenGL3_NewFrame();
     pub fn ImGui_ImplWin32_NewFrame();
-
-    pub fn ImGui_ImplOpenGL3_RenderDrawData(draw_data: *mut imgui::DrawData);
 }
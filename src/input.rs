@@ -0,0 +1,99 @@
+use nalgebra_glm::Vec2;
+
+/// A platform-neutral input event, translated from native events by each platform backend.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputEvent {
+    /// The pointer moved to `position` (in pixel space) with `buttons` held down.
+    PointerMove {
+        position: Vec2,
+        buttons: MouseButtons,
+    },
+    PointerDown {
+        position: Vec2,
+        button: MouseButton,
+    },
+    PointerUp {
+        position: Vec2,
+        button: MouseButton,
+    },
+    /// The mouse wheel was scrolled by `delta` notches, `y` is positive when scrolling away from the user and `x` is
+    /// positive when scrolling right. High resolution wheels and touchpads report fractional notches.
+    Wheel {
+        position: Vec2,
+        delta: Vec2,
+        modifiers: Modifiers,
+    },
+    KeyDown {
+        key: Key,
+        modifiers: Modifiers,
+    },
+    KeyUp {
+        key: Key,
+        modifiers: Modifiers,
+    },
+    /// The global hotkey registered with [`crate::platform::Platform::register_hotkey`] was pressed.
+    Hotkey,
+    /// The client area of the window was resized to the given size in pixels.
+    Resize {
+        width: u32,
+        height: u32,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+}
+
+/// The set of mouse buttons that are held down.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MouseButtons {
+    pub left: bool,
+    pub right: bool,
+    pub middle: bool,
+}
+
+/// The set of modifier keys that are held down.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl Modifiers {
+    pub const NONE: Self = Self {
+        ctrl: false,
+        shift: false,
+        alt: false,
+    };
+
+    pub const ALT: Self = Self {
+        alt: true,
+        ..Self::NONE
+    };
+}
+
+/// A physical key, independent of the platform's key codes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Escape,
+    Enter,
+    Space,
+    Tab,
+    Backspace,
+    Left,
+    Right,
+    Up,
+    Down,
+    Plus,
+    Minus,
+    /// A letter key, always uppercase (`'A'..='Z'`).
+    Letter(char),
+    /// A digit key on the number row or keypad (`0..=9`).
+    Digit(u8),
+    /// A function key, `Function(1)` is F1.
+    Function(u8),
+}
@@ -0,0 +1,233 @@
+use nalgebra_glm::RealNumber;
+
+/// Overshoot of [`Curve::Back`], pulling back by about 10% (<https://easings.net/#easeInBack>).
+const BACK_OVERSHOOT: f32 = 1.70158;
+
+/// The shape of an easing curve, see [`Easing`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Curve {
+    Quad,
+    Cubic,
+    Expo,
+    /// Pulls back a little before moving.
+    Back,
+    /// Wobbles like a rubber band before moving.
+    Elastic,
+}
+
+impl Curve {
+    /// Eases in, starting slowly, mapping 0 to 0 and 1 to 1.
+    fn ease_in<T: RealNumber>(self, t: T) -> T {
+        let (zero, one) = (T::zero(), T::one());
+        let constant = |value: f32| T::from_f32(value).unwrap();
+
+        match self {
+            Curve::Quad => t * t,
+            Curve::Cubic => t * t * t,
+            Curve::Expo | Curve::Elastic if t <= zero => zero,
+            Curve::Expo | Curve::Elastic if t >= one => one,
+            Curve::Expo => constant(2.0).powf(constant(10.0) * (t - one)),
+            Curve::Back => {
+                let overshoot = constant(BACK_OVERSHOOT);
+
+                t * t * ((overshoot + one) * t - overshoot)
+            }
+            Curve::Elastic => {
+                -constant(2.0).powf(constant(10.0) * (t - one))
+                    * ((constant(10.0) * t - constant(10.75)) * T::two_pi() / constant(3.0)).sin()
+            }
+        }
+    }
+}
+
+/// Maps the progress of a tween between 0 and 1 to how far the value has moved towards the target
+/// (<https://easings.net/>).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Easing {
+    Linear,
+    /// Starts slowly.
+    In(Curve),
+    /// Ends slowly.
+    Out(Curve),
+    /// Starts and ends slowly.
+    InOut(Curve),
+    /// A CSS style cubic Bézier curve from (0, 0) to (1, 1) with the control points (x1, y1) and (x2, y2), the x
+    /// coordinates must be between 0 and 1 (<https://developer.mozilla.org/en-US/docs/Web/CSS/easing-function>).
+    CubicBezier(f32, f32, f32, f32),
+}
+
+impl Easing {
+    pub fn apply<T: RealNumber>(&self, t: T) -> T {
+        let one = T::one();
+        let two = T::from_f32(2.0).unwrap();
+
+        match *self {
+            Easing::Linear => t,
+            Easing::In(curve) => curve.ease_in(t),
+            Easing::Out(curve) => one - curve.ease_in(one - t),
+            Easing::InOut(curve) if t < one / two => curve.ease_in(two * t) / two,
+            Easing::InOut(curve) => one - curve.ease_in(two - two * t) / two,
+            Easing::CubicBezier(x1, y1, x2, y2) => {
+                let [x1, y1, x2, y2] = [x1, y1, x2, y2].map(|value| T::from_f32(value).unwrap());
+
+                cubic_bezier(y1, y2, solve_cubic_bezier(x1, x2, t))
+            }
+        }
+    }
+}
+
+/// One coordinate of the cubic Bézier curve from 0 to 1 with the control points `p1` and `p2` at `s`.
+fn cubic_bezier<T: RealNumber>(p1: T, p2: T, s: T) -> T {
+    let three = T::from_f32(3.0).unwrap();
+    let rest = T::one() - s;
+
+    three * rest * rest * s * p1 + three * rest * s * s * p2 + s * s * s
+}
+
+/// Finds the `s` where the cubic Bézier curve with the control points `p1` and `p2` is at `t`, which only has one
+/// solution as long as both are between 0 and 1.
+fn solve_cubic_bezier<T: RealNumber>(p1: T, p2: T, t: T) -> T {
+    let (zero, one) = (T::zero(), T::one());
+    let three = T::from_f32(3.0).unwrap();
+    let tolerance = T::from_f32(1e-6).unwrap();
+
+    let t = t.max(zero).min(one);
+
+    // Newton's method converges fast unless the curve is flat, in which case bisecting still does.
+    let mut s = t;
+    for _ in 0..8 {
+        let error = cubic_bezier(p1, p2, s) - t;
+        if error.abs() < tolerance {
+            return s;
+        }
+
+        let rest = one - s;
+        let slope = three * rest * rest * p1
+            + three * (one + one) * rest * s * (p2 - p1)
+            + three * s * s * (one - p2);
+        if slope.abs() < tolerance {
+            break;
+        }
+
+        s -= error / slope;
+    }
+
+    let (mut low, mut high) = (zero, one);
+    s = t;
+    for _ in 0..64 {
+        let error = cubic_bezier(p1, p2, s) - t;
+        if error.abs() < tolerance {
+            break;
+        }
+
+        if error < zero {
+            low = s;
+        } else {
+            high = s;
+        }
+        s = (low + high) / (one + one);
+    }
+
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CURVES: [Curve; 5] = [
+        Curve::Quad,
+        Curve::Cubic,
+        Curve::Expo,
+        Curve::Back,
+        Curve::Elastic,
+    ];
+
+    fn easings() -> Vec<Easing> {
+        let mut easings = vec![
+            Easing::Linear,
+            Easing::CubicBezier(0.25, 0.1, 0.25, 1.0),
+            Easing::CubicBezier(0.0, 0.0, 1.0, 1.0),
+            Easing::CubicBezier(0.68, -0.6, 0.32, 1.6),
+        ];
+        for curve in CURVES {
+            easings.extend([Easing::In(curve), Easing::Out(curve), Easing::InOut(curve)]);
+        }
+
+        easings
+    }
+
+    #[test]
+    fn easings_start_at_0_and_end_at_1() {
+        for easing in easings() {
+            assert!(easing.apply(0.0_f64).abs() < 1e-6, "{:?}", easing);
+            assert!((easing.apply(1.0_f64) - 1.0).abs() < 1e-6, "{:?}", easing);
+        }
+    }
+
+    #[test]
+    fn easings_are_continuous() {
+        for easing in easings() {
+            for i in 0..1000 {
+                let (t, next_t) = (i as f64 / 1000.0, (i + 1) as f64 / 1000.0);
+
+                assert!(
+                    (easing.apply(next_t) - easing.apply(t)).abs() < 0.05,
+                    "{:?} at {}",
+                    easing,
+                    t
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn easing_in_starts_slowly_and_easing_out_ends_slowly() {
+        for curve in [Curve::Quad, Curve::Cubic, Curve::Expo] {
+            assert!(Easing::In(curve).apply(0.5_f64) < 0.5, "{:?}", curve);
+            assert!(Easing::Out(curve).apply(0.5_f64) > 0.5, "{:?}", curve);
+            assert!((Easing::InOut(curve).apply(0.5_f64) - 0.5).abs() < 1e-9);
+            assert!(Easing::InOut(curve).apply(0.25_f64) < 0.25, "{:?}", curve);
+            assert!(Easing::InOut(curve).apply(0.75_f64) > 0.75, "{:?}", curve);
+        }
+
+        assert_eq!(Easing::In(Curve::Quad).apply(0.5_f64), 0.25);
+        assert_eq!(Easing::Out(Curve::Cubic).apply(0.5_f64), 0.875);
+        assert!((Easing::In(Curve::Expo).apply(0.9_f64) - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn back_and_elastic_overshoot() {
+        for curve in [Curve::Back, Curve::Elastic] {
+            let values: Vec<f64> = (0..=100)
+                .map(|i| Easing::In(curve).apply(i as f64 / 100.0))
+                .collect();
+            assert!(values.iter().any(|&value| value < -0.05), "{:?}", curve);
+
+            let values: Vec<f64> = (0..=100)
+                .map(|i| Easing::Out(curve).apply(i as f64 / 100.0))
+                .collect();
+            assert!(values.iter().any(|&value| value > 1.05), "{:?}", curve);
+        }
+    }
+
+    #[test]
+    fn cubic_bezier_matches_its_special_cases() {
+        // The control points on the diagonal make it linear.
+        let linear = Easing::CubicBezier(1.0 / 3.0, 1.0 / 3.0, 2.0 / 3.0, 2.0 / 3.0);
+        // CSS' ease-in-out.
+        let ease_in_out = Easing::CubicBezier(0.42, 0.0, 0.58, 1.0);
+
+        for i in 0..=20 {
+            let t = i as f64 / 20.0;
+
+            assert!((linear.apply(t) - t).abs() < 1e-5);
+            assert!((ease_in_out.apply(t) + ease_in_out.apply(1.0 - t) - 1.0).abs() < 1e-5);
+        }
+
+        assert!(ease_in_out.apply(0.25_f64) < 0.2);
+        // The curve of CSS' ease, for which the x coordinates are flat at both ends.
+        let ease = Easing::CubicBezier(0.25, 0.1, 0.25, 1.0);
+        assert!((ease.apply(0.5_f64) - 0.8024).abs() < 1e-3);
+    }
+}
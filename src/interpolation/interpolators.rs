@@ -1,8 +1,22 @@
 use nalgebra_glm as glm;
 use nalgebra_glm::{RealNumber, TVec};
 
+use super::Easing;
+
 pub trait Interpolator<T: RealNumber, const R: usize> {
     fn interpolate(&mut self, current: TVec<T, R>, target: TVec<T, R>, dt: T) -> TVec<T, R>;
+
+    /// Called when the target changes, while the value is still at `current`.
+    fn on_set_target(&mut self, _current: TVec<T, R>, _target: TVec<T, R>) {}
+
+    /// Called when the value jumps from `previous` to `current` without interpolating.
+    fn on_set_current(&mut self, _previous: TVec<T, R>, _current: TVec<T, R>) {}
+
+    /// Whether the value has reached the target and won't change until either of them does. Interpolators which
+    /// only ever approach the target are finished once they're within `tolerance` of it.
+    fn is_finished(&self, current: TVec<T, R>, target: TVec<T, R>, tolerance: T) -> bool {
+        (current - target).abs().max() <= tolerance
+    }
 }
 
 pub struct ExponentialSmoothing<T> {
@@ -35,24 +49,174 @@ impl<T: RealNumber, const R: usize> Interpolator<T, R> for ExponentialSmoothing<
 }
 
 pub struct LinearInterpolation<T> {
-    k: T,
+    length_sec: T,
     time: T,
 }
 
 impl<T: RealNumber> LinearInterpolation<T> {
     fn new(length_sec: T) -> Self {
         Self {
-            k: T::one() / length_sec,
-            time: T::zero(),
+            length_sec,
+            time: length_sec,
         }
     }
 }
 
 impl<T: RealNumber, const R: usize> Interpolator<T, R> for LinearInterpolation<T> {
     fn interpolate(&mut self, current: TVec<T, R>, target: TVec<T, R>, dt: T) -> TVec<T, R> {
+        let time_left = self.length_sec - self.time;
+        if dt >= time_left {
+            self.time = self.length_sec;
+
+            return target;
+        }
+
+        // Moving at a constant speed, the rest of the way takes the rest of the time.
         self.time += dt;
 
-        glm::lerp(&current, &target, self.k * self.time)
+        glm::lerp(&current, &target, dt / time_left)
+    }
+
+    fn on_set_target(&mut self, _current: TVec<T, R>, _target: TVec<T, R>) {
+        self.time = T::zero();
+    }
+
+    fn is_finished(&self, _current: TVec<T, R>, _target: TVec<T, R>, _tolerance: T) -> bool {
+        self.time >= self.length_sec
+    }
+}
+
+pub struct Tween<T: RealNumber, const R: usize> {
+    length_sec: T,
+    easing: Easing,
+    start: TVec<T, R>,
+    time: T,
+}
+
+impl<T: RealNumber, const R: usize> Tween<T, R> {
+    /// Create a new interpolator which moves from the value at the time the target is set to the target in
+    /// `length_sec` seconds, following the `easing` curve.
+    pub fn new(length_sec: T, easing: Easing) -> Self {
+        Self {
+            length_sec,
+            easing,
+            start: TVec::zeros(),
+            time: length_sec,
+        }
+    }
+}
+
+impl<T: RealNumber, const R: usize> Interpolator<T, R> for Tween<T, R> {
+    fn interpolate(&mut self, _current: TVec<T, R>, target: TVec<T, R>, dt: T) -> TVec<T, R> {
+        self.time = (self.time + dt).min(self.length_sec);
+        if self.time >= self.length_sec {
+            return target;
+        }
+
+        self.start + (target - self.start) * self.easing.apply(self.time / self.length_sec)
+    }
+
+    fn on_set_target(&mut self, current: TVec<T, R>, _target: TVec<T, R>) {
+        self.start = current;
+        self.time = T::zero();
+    }
+
+    fn on_set_current(&mut self, previous: TVec<T, R>, current: TVec<T, R>) {
+        // Keeps going as if it had started from where the value jumped to.
+        self.start += current - previous;
+    }
+
+    fn is_finished(&self, _current: TVec<T, R>, _target: TVec<T, R>, _tolerance: T) -> bool {
+        self.time >= self.length_sec
+    }
+}
+
+pub struct SpringDamper<T: RealNumber, const R: usize> {
+    angular_frequency: T,
+    damping_ratio: T,
+    velocity: TVec<T, R>,
+}
+
+impl<T: RealNumber, const R: usize> SpringDamper<T, R> {
+    /// Create a new interpolator which pulls the value towards the target like a damped spring
+    /// (<https://en.wikipedia.org/wiki/Harmonic_oscillator#Damped_harmonic_oscillator>). It keeps its velocity when the
+    /// target changes, so retargeting halfway through doesn't make the value jerk, but stops when the value jumps.
+    /// - `frequency`: The undamped natural frequency of the spring in Hz, higher is faster.
+    /// - `damping_ratio`: 1 for critical damping, which settles the fastest without overshooting. Lower values
+    ///   overshoot and oscillate, higher values settle slower.
+    pub fn new(frequency: T, damping_ratio: T) -> Self {
+        Self {
+            angular_frequency: frequency * T::two_pi(),
+            damping_ratio,
+            velocity: TVec::zeros(),
+        }
+    }
+
+    /// Create a new critically damped spring, see [`SpringDamper::new`].
+    pub fn critically_damped(frequency: T) -> Self {
+        Self::new(frequency, T::one())
+    }
+}
+
+impl<T: RealNumber, const R: usize> Interpolator<T, R> for SpringDamper<T, R> {
+    fn interpolate(&mut self, current: TVec<T, R>, target: TVec<T, R>, dt: T) -> TVec<T, R> {
+        let omega = self.angular_frequency;
+        let zeta = self.damping_ratio;
+        let one = T::one();
+
+        // The exact solution for the offset from the target `x` and the velocity `v` after `dt`, so the result doesn't
+        // depend on the frame rate. Both are linear in their initial values: `x = x0 * xx + v0 * xv` and
+        // `v = x0 * vx + v0 * vv`.
+        let (xx, xv, vx, vv) = if (zeta - one).abs() < T::from_f32(1e-4).unwrap() {
+            let decay = (-omega * dt).exp();
+
+            (
+                decay * (one + omega * dt),
+                decay * dt,
+                -decay * omega * omega * dt,
+                decay * (one - omega * dt),
+            )
+        } else if zeta < one {
+            let decay_rate = zeta * omega;
+            let damped_omega = omega * (one - zeta * zeta).sqrt();
+            let decay = (-decay_rate * dt).exp();
+            let (sin, cos) = (damped_omega * dt).sin_cos();
+
+            (
+                decay * (cos + decay_rate * sin / damped_omega),
+                decay * sin / damped_omega,
+                -decay * sin * omega * omega / damped_omega,
+                decay * (cos - decay_rate * sin / damped_omega),
+            )
+        } else {
+            let root = (zeta * zeta - one).sqrt();
+            let (rate, other_rate) = (-omega * (zeta - root), -omega * (zeta + root));
+            let (decay, other_decay) = ((rate * dt).exp(), (other_rate * dt).exp());
+            let x_difference = (other_decay - decay) / (other_rate - rate);
+            let v_difference = (other_rate * other_decay - rate * decay) / (other_rate - rate);
+
+            (
+                decay - rate * x_difference,
+                x_difference,
+                rate * decay - rate * v_difference,
+                v_difference,
+            )
+        };
+
+        let (offset, velocity) = (current - target, self.velocity);
+        self.velocity = offset * vx + velocity * vv;
+
+        target + offset * xx + velocity * xv
+    }
+
+    fn on_set_current(&mut self, _previous: TVec<T, R>, _current: TVec<T, R>) {
+        self.velocity = TVec::zeros();
+    }
+
+    /// Also waits for the spring to slow down, so it isn't finished while it's swinging past the target.
+    fn is_finished(&self, current: TVec<T, R>, target: TVec<T, R>, tolerance: T) -> bool {
+        (current - target).abs().max() <= tolerance
+            && self.velocity.abs().max() <= tolerance * self.angular_frequency
     }
 }
 
@@ -60,7 +224,7 @@ impl<T: RealNumber, const R: usize> Interpolator<T, R> for LinearInterpolation<T
 mod tests {
     use glm::vec2;
 
-    use crate::interpolation::{InterpolatedScalar, InterpolatedVector};
+    use crate::interpolation::{Curve, InterpolatedScalar, InterpolatedVector};
 
     use super::*;
 
@@ -94,6 +258,128 @@ mod tests {
         assert_eq!(var.current(), vec2(5.0, 2.5));
     }
 
+    #[test]
+    fn linear_interpolation_stops_at_the_target() {
+        let mut var = InterpolatedScalar::new_zeroed(LinearInterpolation::new(1.0_f32));
+        assert!(var.is_finished(0.0));
+        var.set_target(10.0);
+
+        var.update(0.75);
+        assert_eq!(var.current(), 7.5);
+        assert!(!var.is_finished(0.0));
+
+        var.update(0.75);
+        var.update(0.75);
+        assert_eq!(var.current(), 10.0);
+        assert!(var.is_finished(0.0));
+
+        // Starts over from where it is, taking the whole time again.
+        var.set_target(20.0);
+        var.update(0.5);
+        assert_eq!(var.current(), 15.0);
+    }
+
+    #[test]
+    fn tween_follows_its_easing_from_where_it_was_retargeted() {
+        let mut var = InterpolatedScalar::new_zeroed(Tween::new(2.0_f64, Easing::In(Curve::Quad)));
+        assert!(var.is_finished(0.0));
+        var.set_target(10.0);
+
+        var.update(1.0);
+        assert!((var.current() - 2.5).abs() < 1e-9);
+
+        var.set_target(2.5 + 4.0);
+        var.update(1.0);
+        assert!((var.current() - 3.5).abs() < 1e-9);
+        assert!(!var.is_finished(0.0));
+
+        var.update(1.5);
+        assert_eq!(var.current(), 6.5);
+        assert!(var.is_finished(0.0));
+    }
+
+    #[test]
+    fn tween_overshoots_with_back_easing_and_lands_on_the_target() {
+        let mut var = InterpolatedVector::new_zeroed(Tween::new(1.0_f32, Easing::Out(Curve::Back)));
+        var.set_target(vec2(10.0, -10.0));
+
+        let mut max = 0.0_f32;
+        for _ in 0..50 {
+            var.update(0.025);
+            max = max.max(var.current().x);
+        }
+
+        assert!(max > 10.5, "{}", max);
+        assert_eq!(var.current(), vec2(10.0, -10.0));
+        assert!(var.is_finished(0.0));
+    }
+
+    #[test]
+    fn tween_carries_on_after_the_value_jumps() {
+        let mut var = InterpolatedScalar::new_zeroed(Tween::new(1.0_f64, Easing::Linear));
+        var.set_target(10.0);
+        var.update(0.5);
+        assert_eq!(var.current(), 5.0);
+
+        // As if it had started 20 further away.
+        var.set_current(25.0);
+        var.update(0.25);
+
+        assert!((var.current() - 12.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn jumping_stops_a_spring() {
+        let mut var = InterpolatedScalar::new_zeroed(SpringDamper::critically_damped(1.0_f32));
+        var.set_target(10.0);
+        var.update(0.1);
+
+        var.set_current(10.0);
+        var.update(0.1);
+
+        assert_eq!(var.current(), 10.0);
+        assert!(var.is_finished(0.0));
+    }
+
+    #[test]
+    fn exponential_smoothing_finishes_within_the_tolerance() {
+        let mut var = InterpolatedScalar::new_zeroed(ExponentialSmoothing::new(0.5_f32, 2.5));
+        var.set_target(10.0);
+
+        var.update(0.5);
+        assert!(!var.is_finished(1e-3));
+
+        for _ in 0..600 {
+            var.update(1.0 / 60.0);
+        }
+
+        assert!(var.is_finished(1e-3));
+    }
+
+    #[test]
+    fn spring_finishes_once_it_has_slowed_down() {
+        let mut var = InterpolatedScalar::new_zeroed(SpringDamper::new(1.0_f32, 0.2));
+        var.set_target(10.0);
+
+        // Swinging past the target.
+        let mut previous = 0.0;
+        loop {
+            var.update(1e-4);
+            if previous < 10.0 && var.current() >= 10.0 {
+                break;
+            }
+            previous = var.current();
+        }
+        assert!((var.current() - 10.0).abs() < 0.1);
+        assert!(!var.is_finished(0.1));
+
+        for _ in 0..1200 {
+            var.update(1.0 / 60.0);
+        }
+
+        assert!(var.is_finished(1e-3));
+    }
+
     #[test]
     fn exponential_smoothing_with_initial_value() {
         let mut var = InterpolatedVector::new(vec2(1.0, 2.0), ExponentialSmoothing::new(1.0, 5.0));
@@ -103,4 +389,89 @@ mod tests {
 
         assert!((var.current() - vec2(10.0, 5.0)).abs() < vec2(1e-3, 1e-3));
     }
+
+    #[test]
+    fn critically_damped_spring_settles_without_overshooting() {
+        let mut var = InterpolatedScalar::new_zeroed(SpringDamper::critically_damped(1.0_f32));
+        var.set_target(10.0);
+
+        let mut previous = 0.0;
+        for _ in 0..300 {
+            var.update(0.01);
+
+            assert!(var.current() >= previous && var.current() <= 10.0);
+            previous = var.current();
+        }
+
+        assert!((var.current() - 10.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn underdamped_spring_overshoots_and_overdamped_spring_does_not() {
+        let mut underdamped = InterpolatedScalar::new_zeroed(SpringDamper::new(1.0_f32, 0.2));
+        let mut overdamped = InterpolatedScalar::new_zeroed(SpringDamper::new(1.0_f32, 3.0));
+        underdamped.set_target(10.0);
+        overdamped.set_target(10.0);
+
+        let mut underdamped_max = 0.0_f32;
+        for _ in 0..1000 {
+            underdamped.update(0.01);
+            overdamped.update(0.01);
+
+            underdamped_max = underdamped_max.max(underdamped.current());
+            assert!(overdamped.current() < 10.0);
+        }
+
+        assert!(underdamped_max > 15.0, "{}", underdamped_max);
+        assert!((underdamped.current() - 10.0).abs() < 1e-3);
+        assert!((overdamped.current() - 10.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn spring_does_not_depend_on_the_frame_rate() {
+        for damping_ratio in [0.3, 1.0, 2.0] {
+            let mut coarse =
+                InterpolatedVector::new_zeroed(SpringDamper::new(1.0_f64, damping_ratio));
+            let mut fine =
+                InterpolatedVector::new_zeroed(SpringDamper::new(1.0_f64, damping_ratio));
+
+            // Retargeting halfway through, while the velocity matters too.
+            for (target, duration) in [(vec2(10.0, -5.0), 0.5), (vec2(-3.0, 2.0), 0.25)] {
+                coarse.set_target(target);
+                fine.set_target(target);
+
+                coarse.update(duration);
+                for _ in 0..(duration * 1000.0) as usize {
+                    fine.update(0.001);
+                }
+
+                assert!(
+                    (coarse.current() - fine.current()).abs() < vec2(1e-6, 1e-6),
+                    "{} != {}",
+                    coarse.current(),
+                    fine.current()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn retargeting_a_spring_keeps_its_velocity() {
+        let mut var = InterpolatedScalar::new_zeroed(SpringDamper::critically_damped(2.0_f64));
+        let dt = 1e-4;
+        var.set_target(10.0);
+        var.update(0.1);
+
+        let before = var.current();
+        var.update(dt);
+        let velocity = (var.current() - before) / dt;
+
+        var.set_target(20.0);
+        let before = var.current();
+        var.update(dt);
+        let velocity_after_retargeting = (var.current() - before) / dt;
+
+        assert!(velocity > 10.0);
+        assert!((velocity_after_retargeting - velocity).abs() < 0.01 * velocity);
+    }
 }
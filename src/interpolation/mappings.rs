@@ -0,0 +1,66 @@
+use nalgebra_glm::RealNumber;
+
+/// Maps values to the space they're interpolated in and back.
+pub trait Mapping<T> {
+    fn to_interpolated(value: T) -> T;
+    fn from_interpolated(value: T) -> T;
+}
+
+/// Interpolates values as they are.
+pub struct Identity;
+
+impl<T> Mapping<T> for Identity {
+    fn to_interpolated(value: T) -> T {
+        value
+    }
+
+    fn from_interpolated(value: T) -> T {
+        value
+    }
+}
+
+/// Interpolates the logarithm of positive values, so multiplying by the same amount always takes the same time.
+pub struct Logarithmic;
+
+impl<T: RealNumber> Mapping<T> for Logarithmic {
+    fn to_interpolated(value: T) -> T {
+        value.ln()
+    }
+
+    fn from_interpolated(value: T) -> T {
+        value.exp()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interpolation::{ExponentialSmoothing, InterpolatedScalar};
+
+    use super::*;
+
+    #[test]
+    fn logarithmic_interpolation_is_scale_invariant() {
+        let mut small = InterpolatedScalar::<_, _, Logarithmic>::new_mapped(
+            1.0_f32,
+            ExponentialSmoothing::new(1.0, 2.0_f32.log10()),
+        );
+        let mut large = InterpolatedScalar::<_, _, Logarithmic>::new_mapped(
+            100.0_f32,
+            ExponentialSmoothing::new(1.0, 2.0_f32.log10()),
+        );
+        small.set_target(4.0);
+        large.set_target(400.0);
+
+        // Covers half of the remaining distance each second.
+        small.update(1.0);
+        large.update(1.0);
+
+        // Half of the way in log space is the geometric mean, a single doubling.
+        assert!((small.current() - 2.0).abs() < 1e-4, "{}", small.current());
+        assert!(
+            (large.current() - 200.0).abs() < 1e-2,
+            "{}",
+            large.current()
+        );
+    }
+}
@@ -1,11 +1,17 @@
+use std::marker::PhantomData;
+
 use glm::RealNumber;
 use nalgebra_glm as glm;
 use nalgebra_glm::{vec1, TVec};
 
+mod easing;
 mod interpolators;
+mod mappings;
 
+pub use easing::{Curve, Easing};
 use interpolators::Interpolator;
-pub use interpolators::{ExponentialSmoothing, LinearInterpolation};
+pub use interpolators::{ExponentialSmoothing, LinearInterpolation, SpringDamper, Tween};
+pub use mappings::{Identity, Logarithmic, Mapping};
 
 // TODO: Is there a way to get make this generic cleaner, getting rid of R?
 pub struct InterpolatedVector<T: RealNumber, const R: usize, I: Interpolator<T, R>> {
@@ -28,9 +34,16 @@ impl<T: RealNumber, const R: usize, I: Interpolator<T, R>> InterpolatedVector<T,
     }
 
     pub fn set_target(&mut self, target: TVec<T, R>) {
+        self.interpolator.on_set_target(self.current, target);
         self.target = target;
     }
 
+    /// Jumps to `current` without interpolating.
+    pub fn set_current(&mut self, current: TVec<T, R>) {
+        self.interpolator.on_set_current(self.current, current);
+        self.current = current;
+    }
+
     pub fn update(&mut self, dt: T) -> TVec<T, R> {
         self.current = self.interpolator.interpolate(self.current, self.target, dt);
 
@@ -44,21 +57,46 @@ impl<T: RealNumber, const R: usize, I: Interpolator<T, R>> InterpolatedVector<T,
     pub fn target(&self) -> TVec<T, R> {
         self.target
     }
+
+    /// Whether the value has reached the target, or is within `tolerance` of it, see [`Interpolator::is_finished`].
+    pub fn is_finished(&self, tolerance: T) -> bool {
+        self.interpolator
+            .is_finished(self.current, self.target, tolerance)
+    }
 }
 
-pub struct InterpolatedScalar<T: RealNumber, I: Interpolator<T, 1>>(InterpolatedVector<T, 1, I>);
+/// A scalar which is interpolated in the space `M` maps it to, eg. [`Logarithmic`] to interpolate a zoom factor by
+/// ratios rather than differences.
+pub struct InterpolatedScalar<T: RealNumber, I: Interpolator<T, 1>, M: Mapping<T> = Identity>(
+    InterpolatedVector<T, 1, I>,
+    PhantomData<M>,
+);
 
 impl<T: RealNumber, I: Interpolator<T, 1>> InterpolatedScalar<T, I> {
     pub fn new(initial: T, interpolator: I) -> Self {
-        Self(InterpolatedVector::new(vec1(initial), interpolator))
+        Self::new_mapped(initial, interpolator)
     }
 
     pub fn new_zeroed(interpolator: I) -> Self {
         Self::new(T::zero(), interpolator)
     }
+}
+
+impl<T: RealNumber, I: Interpolator<T, 1>, M: Mapping<T>> InterpolatedScalar<T, I, M> {
+    pub fn new_mapped(initial: T, interpolator: I) -> Self {
+        Self(
+            InterpolatedVector::new(vec1(M::to_interpolated(initial)), interpolator),
+            PhantomData,
+        )
+    }
 
     pub fn set_target(&mut self, target: T) {
-        self.0.set_target(vec1(target));
+        self.0.set_target(vec1(M::to_interpolated(target)));
+    }
+
+    /// Jumps to `current` without interpolating.
+    pub fn set_current(&mut self, current: T) {
+        self.0.set_current(vec1(M::to_interpolated(current)));
     }
 
     pub fn update(&mut self, dt: T) {
@@ -66,10 +104,15 @@ impl<T: RealNumber, I: Interpolator<T, 1>> InterpolatedScalar<T, I> {
     }
 
     pub fn current(&self) -> T {
-        self.0.current().x
+        M::from_interpolated(self.0.current().x)
     }
 
     pub fn target(&self) -> T {
-        self.0.target().x
+        M::from_interpolated(self.0.target().x)
+    }
+
+    /// Whether the value has reached the target, or is within `tolerance` of it in the space `M` maps it to.
+    pub fn is_finished(&self, tolerance: T) -> bool {
+        self.0.is_finished(tolerance)
     }
 }
@@ -1,215 +1,70 @@
 #![feature(backtrace)]
 
+#[cfg(all(feature = "headless", not(target_os = "linux")))]
+compile_error!("the `headless` feature renders through EGL, which is only supported on Linux");
+
+use std::path::PathBuf;
+
+use args::Args;
+
+mod bookmarks;
 mod camera;
 mod highlighter;
+mod minimap;
 mod screenshot;
 mod zoomer;
 
+mod args;
 mod console;
 mod ffi;
+mod flight;
 mod gl;
+mod image;
 mod imgui_impl;
+mod input;
 mod interpolation;
 mod monitors;
+mod platform;
 
-use std::{ptr, time::Instant};
-
-use winapi::{
-    shared::{
-        minwindef::{DWORD, HIWORD, LOWORD, LPARAM, LRESULT, WPARAM},
-        windef::{HWND, POINT, RECT},
-        windowsx::{GET_X_LPARAM, GET_Y_LPARAM},
-        winerror::S_OK,
-    },
-    um::{
-        libloaderapi::GetModuleHandleA,
-        shellscalingapi::{SetProcessDpiAwareness, PROCESS_PER_MONITOR_DPI_AWARE},
-        winuser::*,
-    },
-};
-
-use ffi::c_str_ptr;
-use imgui_impl::*;
-use zoomer::Zoomer;
-
-use crate::gl::wglSwapIntervalEXT;
-
-const WIDTH: i32 = 1920;
-const HEIGHT: i32 = 1080;
+#[cfg(all(test, target_os = "linux"))]
+mod golden_tests;
 
 fn main() {
     console::init();
 
-    let instance = unsafe { GetModuleHandleA(std::ptr::null()) };
-    assert!(!instance.is_null());
-
-    let class = unsafe {
-        RegisterClassExA(&WNDCLASSEXA {
-            cbSize: std::mem::size_of::<WNDCLASSEXA>() as u32,
-            lpfnWndProc: Some(window_proc),
-            hInstance: instance,
-            lpszClassName: c_str_ptr!("ZoomerClass"),
-            hCursor: LoadCursorW(std::ptr::null_mut(), IDC_ARROW),
-            ..Default::default()
-        })
-    };
-    assert!(class != 0);
-
-    let window = unsafe {
-        CreateWindowExA(
-            0,
-            class as usize as *const i8,
-            c_str_ptr!("Zoomer"),
-            WS_OVERLAPPEDWINDOW,
-            CW_USEDEFAULT,
-            CW_USEDEFAULT,
-            WIDTH,
-            HEIGHT,
-            std::ptr::null_mut(),
-            std::ptr::null_mut(),
-            instance,
-            std::ptr::null_mut(),
-        )
-    };
-    assert!(!window.is_null());
-
-    let hdc = unsafe { GetDC(window) };
-    assert!(!hdc.is_null());
-
-    unsafe {
-        assert_eq!(SetProcessDpiAwareness(PROCESS_PER_MONITOR_DPI_AWARE), S_OK);
-    }
-
-    let mut zoomer = Zoomer::new();
-
-    let (client_width, client_height) = unsafe {
-        let mut rect = RECT::default();
-
-        GetClientRect(window, &mut rect);
-
-        (rect.right - rect.left, rect.bottom - rect.top)
+    let mut args = match Args::parse(std::env::args_os().skip(1)) {
+        Ok(args) => args,
+        Err(error) => {
+            eprintln!("{}\n{}", error, args::USAGE);
+            std::process::exit(1);
+        }
     };
 
-    zoomer.init(window, client_width, client_height);
-
-    // Store a pointer to the zoomer object in the window so that we can access it from the `window_proc`.
-    unsafe {
-        SetWindowLongPtrA(window, GWLP_USERDATA, ptr::addr_of_mut!(zoomer) as isize);
-    }
-
-    // Enable V-Sync. It seems like this is the default, but just in case.
-    unsafe { wglSwapIntervalEXT(1) };
-
-    unsafe {
-        RegisterHotKey(window, 0, MOD_ALT as u32, 'A' as u32);
-        ShowWindow(window, SW_SHOW);
-    }
-
-    let mut message = MSG::default();
-    let mut dt_timer = Instant::now();
-
-    unsafe {
-        'main: loop {
-            while PeekMessageA(&mut message, std::ptr::null_mut(), 0, 0, PM_REMOVE) != 0 {
-                if message.message == WM_QUIT {
-                    break 'main;
-                }
-
-                TranslateMessage(&message);
-                DispatchMessageA(&message);
-            }
-
-            zoomer.render();
-            zoomer.update(dt_timer.elapsed().as_secs_f32());
-
-            dt_timer = Instant::now();
-        }
+    match args.render_path.take() {
+        Some(render_path) => render(args, render_path),
+        None => platform::run(args),
     }
 }
 
-unsafe extern "system" fn window_proc(
-    window: HWND,
-    message: u32,
-    w_param: WPARAM,
-    l_param: LPARAM,
-) -> LRESULT {
-    use winapi::um::winuser::*;
-
-    let zoomer = GetWindowLongPtrA(window, GWLP_USERDATA) as *mut Zoomer;
-
-    if zoomer.is_null() {
-        // zoomer has not been initialized yet.
-        return DefWindowProcA(window, message, w_param, l_param);
-    }
-
-    let zoomer = &mut *zoomer;
-
-    // SetCapture() allows from when mouse is outside of the window to be captured.
-    if ImGui_ImplWin32_WndProcHandler(window, message, w_param, l_param) != 0 {
-        return 1;
-    }
-
-    match message {
-        WM_SIZE => {
-            let width = LOWORD(l_param as DWORD);
-            let height = HIWORD(l_param as DWORD);
-
-            zoomer.on_resize(width, height);
-        }
-        WM_LBUTTONDOWN => {
-            if zoomer.imgui_wants_mouse_events() {
-                return 0;
-            }
-
-            let x = GET_X_LPARAM(l_param);
-            let y = GET_Y_LPARAM(l_param);
-
-            zoomer.on_left_mouse_down(x, y);
-        }
-        WM_LBUTTONUP => {
-            zoomer.on_left_mouse_up();
+/// Renders a single frame of the image offscreen and saves it instead of opening the zoomer.
+#[cfg(feature = "headless")]
+fn render(mut args: Args, render_path: PathBuf) {
+    let image_path = match args.image_path.take() {
+        Some(image_path) => image_path,
+        None => {
+            eprintln!("--render expects an image to render\n{}", args::USAGE);
+            std::process::exit(1);
         }
-        WM_MOUSEMOVE => {
-            if zoomer.imgui_wants_mouse_events() {
-                return 0;
-            }
-
-            let x = GET_X_LPARAM(l_param);
-            let y = GET_Y_LPARAM(l_param);
-
-            zoomer.on_mouse_move(x, y, w_param & MK_LBUTTON != 0);
-        }
-        WM_MOUSEWHEEL => {
-            if zoomer.imgui_wants_mouse_events() {
-                return 0;
-            }
-
-            let delta = GET_WHEEL_DELTA_WPARAM(w_param);
-            let x = GET_X_LPARAM(l_param);
-            let y = GET_Y_LPARAM(l_param);
-
-            let mut point = POINT { x, y };
-            ScreenToClient(window, &mut point);
-
-            zoomer.on_mouse_wheel(delta, point.x, point.y, w_param & MK_CONTROL != 0);
-        }
-        WM_KEYDOWN => {
-            if zoomer.imgui_wants_keyboard_events() {
-                return 0;
-            }
-
-            let key = w_param as u8;
+    };
 
-            zoomer.on_key_down(key);
-        }
-        WM_HOTKEY => {
-            zoomer.on_hotkey();
-        }
-        WM_DESTROY => {
-            PostQuitMessage(0);
-        }
-        _ => return DefWindowProcA(window, message, w_param, l_param),
+    if let Err(error) = platform::headless::render(args, image_path, &render_path) {
+        eprintln!("Unable to render `{}` ({}).", render_path.display(), error);
+        std::process::exit(1);
     }
+}
 
-    0
+#[cfg(not(feature = "headless"))]
+fn render(_args: Args, _render_path: PathBuf) {
+    eprintln!("--render needs zoomer to be built with the `headless` feature");
+    std::process::exit(1);
 }
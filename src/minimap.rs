@@ -0,0 +1,149 @@
+use nalgebra_glm::{vec2, Vec2};
+
+use crate::zoomer::DEFAULT_MINIMAP_SIZE;
+
+/// Gap between the minimap and the edges of the window, in pixels.
+const MARGIN: f32 = 12.0;
+
+/// Which corner of the window the minimap is shown in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MinimapCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// A small overview of the whole capture in a corner of the window, showing which part of it is on the screen.
+pub struct Minimap {
+    is_enabled: bool,
+    corner: MinimapCorner,
+    /// Length of the longer side of the minimap, in pixels.
+    size: f32,
+}
+
+impl Minimap {
+    pub fn new() -> Self {
+        Self {
+            is_enabled: false,
+            corner: MinimapCorner::BottomRight,
+            size: DEFAULT_MINIMAP_SIZE as f32,
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.is_enabled = enabled;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.is_enabled
+    }
+
+    pub fn set_corner(&mut self, corner: MinimapCorner) {
+        self.corner = corner;
+    }
+
+    pub fn corner(&self) -> MinimapCorner {
+        self.corner
+    }
+
+    pub fn set_size(&mut self, size: f32) {
+        self.size = size.max(1.0);
+    }
+
+    pub fn size(&self) -> f32 {
+        self.size
+    }
+
+    /// Returns the top left and bottom right corners of the minimap in pixel space, showing a capture with the given
+    /// aspect ratio in a window of `client_size` pixels.
+    pub fn rect(&self, client_size: Vec2, capture_aspect_ratio: f32) -> (Vec2, Vec2) {
+        let size = if capture_aspect_ratio >= 1.0 {
+            vec2(self.size, self.size / capture_aspect_ratio)
+        } else {
+            vec2(self.size * capture_aspect_ratio, self.size)
+        };
+
+        let far_corner = client_size - size - vec2(MARGIN, MARGIN);
+        let top_left = match self.corner {
+            MinimapCorner::TopLeft => vec2(MARGIN, MARGIN),
+            MinimapCorner::TopRight => vec2(far_corner.x, MARGIN),
+            MinimapCorner::BottomLeft => vec2(MARGIN, far_corner.y),
+            MinimapCorner::BottomRight => far_corner,
+        };
+
+        (top_left, top_left + size)
+    }
+
+    /// Whether the minimap is shown and covers the pixel space point `pixel_coords`.
+    pub fn contains(
+        &self,
+        pixel_coords: Vec2,
+        client_size: Vec2,
+        capture_aspect_ratio: f32,
+    ) -> bool {
+        let (top_left, bottom_right) = self.rect(client_size, capture_aspect_ratio);
+
+        self.is_enabled
+            && (top_left.x..=bottom_right.x).contains(&pixel_coords.x)
+            && (top_left.y..=bottom_right.y).contains(&pixel_coords.y)
+    }
+
+    /// Converts from pixel space to the UV space of the capture shown on the minimap.
+    pub fn pixel_to_uv_space(
+        &self,
+        pixel_coords: Vec2,
+        client_size: Vec2,
+        capture_aspect_ratio: f32,
+    ) -> Vec2 {
+        let (top_left, bottom_right) = self.rect(client_size, capture_aspect_ratio);
+
+        (pixel_coords - top_left).component_div(&(bottom_right - top_left))
+    }
+
+    /// Converts from the UV space of the capture to where it's shown on the minimap in pixel space.
+    pub fn uv_to_pixel_space(
+        &self,
+        uv_coords: Vec2,
+        client_size: Vec2,
+        capture_aspect_ratio: f32,
+    ) -> Vec2 {
+        let (top_left, bottom_right) = self.rect(client_size, capture_aspect_ratio);
+
+        top_left + uv_coords.component_mul(&(bottom_right - top_left))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fits_the_capture_into_the_corner() {
+        let mut minimap = Minimap::new();
+        let client_size = vec2(800.0, 600.0);
+        minimap.set_size(200.0);
+
+        assert_eq!(
+            minimap.rect(client_size, 2.0),
+            (vec2(588.0, 488.0), vec2(788.0, 588.0))
+        );
+
+        minimap.set_corner(MinimapCorner::TopLeft);
+        assert_eq!(
+            minimap.rect(client_size, 0.5),
+            (vec2(12.0, 12.0), vec2(112.0, 212.0))
+        );
+
+        let uv = vec2(0.25, 0.75);
+        let pixel = minimap.uv_to_pixel_space(uv, client_size, 0.5);
+        assert_eq!(pixel, vec2(37.0, 162.0));
+        assert_eq!(minimap.pixel_to_uv_space(pixel, client_size, 0.5), uv);
+
+        // Only while it's shown.
+        assert!(!minimap.contains(pixel, client_size, 0.5));
+        minimap.set_enabled(true);
+        assert!(minimap.contains(pixel, client_size, 0.5));
+        assert!(!minimap.contains(vec2(120.0, 100.0), client_size, 0.5));
+    }
+}
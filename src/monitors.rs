@@ -1,51 +1,118 @@
-use std::ptr;
-
-use winapi::{
-    shared::{
-        minwindef::{BOOL, LPARAM, TRUE},
-        windef::{HDC, HMONITOR, LPRECT},
-    },
-    um::winuser::EnumDisplayMonitors,
-};
-
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Monitor {
+    pub name: String,
+    /// Position of the top left corner in virtual desktop coordinates, which may be negative.
     pub x: i32,
     pub y: i32,
     pub width: u32,
     pub height: u32,
+    /// Ratio of the monitor's DPI to the standard 96 DPI.
+    pub scale_factor: f32,
+    pub is_primary: bool,
 }
 
-unsafe extern "system" fn monitor_enum_proc(
-    _: HMONITOR,
-    _: HDC,
-    rect: LPRECT,
-    monitors: LPARAM,
-) -> BOOL {
-    let monitors = &mut *(monitors as *mut Vec<Monitor>);
-
-    let rect = *rect;
-    monitors.push(Monitor {
-        x: rect.left,
-        y: rect.top,
-        width: (rect.right - rect.left) as u32,
-        height: (rect.bottom - rect.top) as u32,
-    });
-
-    TRUE
+impl Monitor {
+    /// Whether the virtual desktop pixel at (`x`, `y`) is shown on this monitor.
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x
+            && y >= self.y
+            && (x - self.x) < self.width as i32
+            && (y - self.y) < self.height as i32
+    }
 }
 
-pub fn enumerate() -> Vec<Monitor> {
-    let mut monitors = Vec::new();
+/// Returns the smallest rectangle containing all `monitors` as `(x, y, width, height)`. Parts of it may not be covered
+/// by any monitor, for example with stacked monitors of different sizes.
+pub fn bounding_rect(monitors: &[Monitor]) -> (i32, i32, u32, u32) {
+    assert!(!monitors.is_empty(), "no monitors found");
 
-    unsafe {
-        EnumDisplayMonitors(
-            std::ptr::null_mut(),
-            std::ptr::null(),
-            Some(monitor_enum_proc),
-            ptr::addr_of_mut!(monitors) as isize,
-        );
-    }
+    let left = monitors.iter().map(|monitor| monitor.x).min().unwrap();
+    let top = monitors.iter().map(|monitor| monitor.y).min().unwrap();
+    let right = monitors
+        .iter()
+        .map(|monitor| monitor.x + monitor.width as i32)
+        .max()
+        .unwrap();
+    let bottom = monitors
+        .iter()
+        .map(|monitor| monitor.y + monitor.height as i32)
+        .max()
+        .unwrap();
+
+    (left, top, (right - left) as u32, (bottom - top) as u32)
+}
+
+/// Returns the monitor showing the virtual desktop pixel at (`x`, `y`). Falls back to the primary monitor, or the
+/// first one if none is primary, when the point is in a gap between monitors.
+pub fn monitor_at(monitors: &[Monitor], x: i32, y: i32) -> &Monitor {
+    assert!(!monitors.is_empty(), "no monitors found");
 
     monitors
+        .iter()
+        .find(|monitor| monitor.contains(x, y))
+        .or_else(|| monitors.iter().find(|monitor| monitor.is_primary))
+        .unwrap_or(&monitors[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(x: i32, y: i32, width: u32, height: u32) -> Monitor {
+        Monitor {
+            name: String::new(),
+            x,
+            y,
+            width,
+            height,
+            scale_factor: 1.0,
+            is_primary: false,
+        }
+    }
+
+    #[test]
+    fn side_by_side_monitors() {
+        let monitors = [monitor(0, 0, 1920, 1080), monitor(1920, 0, 2560, 1440)];
+
+        assert_eq!(bounding_rect(&monitors), (0, 0, 4480, 1440));
+    }
+
+    #[test]
+    fn stacked_monitors() {
+        let monitors = [monitor(0, 0, 1920, 1080), monitor(320, 1080, 1280, 1024)];
+
+        assert_eq!(bounding_rect(&monitors), (0, 0, 1920, 2104));
+    }
+
+    #[test]
+    fn monitors_left_of_and_above_the_primary() {
+        let monitors = [
+            monitor(0, 0, 1920, 1080),
+            monitor(-1280, 200, 1280, 1024),
+            monitor(0, -1080, 1920, 1080),
+        ];
+
+        assert_eq!(bounding_rect(&monitors), (-1280, -1080, 3200, 2304));
+    }
+
+    #[test]
+    fn monitors_with_a_gap() {
+        let monitors = [monitor(0, 0, 100, 100), monitor(150, 50, 100, 100)];
+
+        assert_eq!(bounding_rect(&monitors), (0, 0, 250, 150));
+        assert!(!monitors.iter().any(|monitor| monitor.contains(120, 75)));
+        assert!(monitors[1].contains(249, 149));
+        assert!(!monitors[1].contains(250, 149));
+    }
+
+    #[test]
+    fn monitor_under_the_cursor() {
+        let mut monitors = [monitor(0, 0, 100, 100), monitor(150, 50, 100, 100)];
+
+        assert_eq!(monitor_at(&monitors, 200, 60), &monitors[1]);
+        assert_eq!(monitor_at(&monitors, 120, 75), &monitors[0]);
+
+        monitors[1].is_primary = true;
+        assert_eq!(monitor_at(&monitors, 120, 75), &monitors[1]);
+    }
 }
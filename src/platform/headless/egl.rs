@@ -0,0 +1,204 @@
+//! Just enough of EGL to create an offscreen OpenGL context without a display server.
+
+use std::ffi::c_void;
+use std::os::raw::c_char;
+
+type EGLBoolean = u32;
+type EGLenum = u32;
+type EGLint = i32;
+type EGLAttrib = isize;
+type EGLDisplay = *mut c_void;
+type EGLConfig = *mut c_void;
+type EGLSurface = *mut c_void;
+type EGLContext = *mut c_void;
+
+// Source: https://www.khronos.org/registry/EGL/api/EGL/egl.h
+const EGL_FALSE: EGLBoolean = 0;
+const EGL_TRUE: EGLint = 1;
+const EGL_NONE: EGLint = 0x3038;
+
+const EGL_ALPHA_SIZE: EGLint = 0x3021;
+const EGL_BLUE_SIZE: EGLint = 0x3022;
+const EGL_GREEN_SIZE: EGLint = 0x3023;
+const EGL_RED_SIZE: EGLint = 0x3024;
+const EGL_SURFACE_TYPE: EGLint = 0x3033;
+const EGL_PBUFFER_BIT: EGLint = 0x0001;
+const EGL_RENDERABLE_TYPE: EGLint = 0x3040;
+const EGL_OPENGL_BIT: EGLint = 0x0008;
+
+const EGL_HEIGHT: EGLint = 0x3056;
+const EGL_WIDTH: EGLint = 0x3057;
+
+const EGL_OPENGL_API: EGLenum = 0x30A2;
+
+const EGL_CONTEXT_MAJOR_VERSION: EGLint = 0x3098;
+const EGL_CONTEXT_MINOR_VERSION: EGLint = 0x30FB;
+const EGL_CONTEXT_OPENGL_PROFILE_MASK: EGLint = 0x30FD;
+const EGL_CONTEXT_OPENGL_CORE_PROFILE_BIT: EGLint = 0x0001;
+const EGL_CONTEXT_OPENGL_DEBUG: EGLint = 0x31B0;
+
+// https://www.khronos.org/registry/EGL/extensions/MESA/EGL_MESA_platform_surfaceless.txt
+const EGL_PLATFORM_SURFACELESS_MESA: EGLenum = 0x31DD;
+
+#[link(name = "EGL")]
+extern "C" {
+    fn eglGetError() -> EGLint;
+    fn eglGetDisplay(native_display: *mut c_void) -> EGLDisplay;
+    fn eglGetPlatformDisplay(
+        platform: EGLenum,
+        native_display: *mut c_void,
+        attrib_list: *const EGLAttrib,
+    ) -> EGLDisplay;
+    fn eglInitialize(display: EGLDisplay, major: *mut EGLint, minor: *mut EGLint) -> EGLBoolean;
+    fn eglBindAPI(api: EGLenum) -> EGLBoolean;
+    fn eglChooseConfig(
+        display: EGLDisplay,
+        attrib_list: *const EGLint,
+        configs: *mut EGLConfig,
+        config_size: EGLint,
+        config_count: *mut EGLint,
+    ) -> EGLBoolean;
+    fn eglCreatePbufferSurface(
+        display: EGLDisplay,
+        config: EGLConfig,
+        attrib_list: *const EGLint,
+    ) -> EGLSurface;
+    fn eglCreateContext(
+        display: EGLDisplay,
+        config: EGLConfig,
+        share_context: EGLContext,
+        attrib_list: *const EGLint,
+    ) -> EGLContext;
+    fn eglMakeCurrent(
+        display: EGLDisplay,
+        draw: EGLSurface,
+        read: EGLSurface,
+        context: EGLContext,
+    ) -> EGLBoolean;
+    fn eglSwapBuffers(display: EGLDisplay, surface: EGLSurface) -> EGLBoolean;
+    fn eglDestroySurface(display: EGLDisplay, surface: EGLSurface) -> EGLBoolean;
+    fn eglDestroyContext(display: EGLDisplay, context: EGLContext) -> EGLBoolean;
+
+    pub fn eglGetCurrentContext() -> EGLContext;
+    pub fn eglGetProcAddress(name: *const c_char) -> *const c_void;
+}
+
+/// An OpenGL 3.3 core profile context rendering into a pbuffer. EGL contexts are current per thread, so it must be
+/// used on the thread that created it.
+pub struct Context {
+    display: EGLDisplay,
+    surface: EGLSurface,
+    context: EGLContext,
+}
+
+impl Context {
+    /// Creates a context with a `width` x `height` RGBA pbuffer and makes it current.
+    pub fn new(width: u32, height: u32) -> Self {
+        unsafe {
+            // Prefer Mesa's surfaceless platform, the default one might try to connect to a display server.
+            let mut display = eglGetPlatformDisplay(
+                EGL_PLATFORM_SURFACELESS_MESA,
+                std::ptr::null_mut(),
+                std::ptr::null(),
+            );
+
+            if display.is_null() {
+                display = eglGetDisplay(std::ptr::null_mut());
+            }
+            assert!(!display.is_null(), "unable to get an EGL display");
+
+            let (mut major, mut minor) = (0, 0);
+            assert!(
+                eglInitialize(display, &mut major, &mut minor) != EGL_FALSE,
+                "unable to initialize EGL (error 0x{:X})",
+                eglGetError()
+            );
+            assert!(eglBindAPI(EGL_OPENGL_API) != EGL_FALSE);
+
+            #[rustfmt::skip]
+            let config_attribs = [
+                EGL_SURFACE_TYPE, EGL_PBUFFER_BIT,
+                EGL_RENDERABLE_TYPE, EGL_OPENGL_BIT,
+                EGL_RED_SIZE, 8,
+                EGL_GREEN_SIZE, 8,
+                EGL_BLUE_SIZE, 8,
+                EGL_ALPHA_SIZE, 8,
+                EGL_NONE
+            ];
+
+            let mut config = std::ptr::null_mut();
+            let mut config_count = 0;
+            eglChooseConfig(
+                display,
+                config_attribs.as_ptr(),
+                &mut config,
+                1,
+                &mut config_count,
+            );
+            assert!(config_count > 0, "no suitable EGL framebuffer config found");
+
+            #[rustfmt::skip]
+            let surface_attribs = [
+                EGL_WIDTH, width as EGLint,
+                EGL_HEIGHT, height as EGLint,
+                EGL_NONE
+            ];
+
+            let surface = eglCreatePbufferSurface(display, config, surface_attribs.as_ptr());
+            assert!(
+                !surface.is_null(),
+                "unable to create an EGL pbuffer (error 0x{:X})",
+                eglGetError()
+            );
+
+            #[rustfmt::skip]
+            let context_attribs = [
+                EGL_CONTEXT_MAJOR_VERSION, 3,
+                EGL_CONTEXT_MINOR_VERSION, 3,
+                EGL_CONTEXT_OPENGL_PROFILE_MASK, EGL_CONTEXT_OPENGL_CORE_PROFILE_BIT,
+                EGL_CONTEXT_OPENGL_DEBUG, EGL_TRUE,
+                EGL_NONE
+            ];
+
+            let context = eglCreateContext(
+                display,
+                config,
+                std::ptr::null_mut(),
+                context_attribs.as_ptr(),
+            );
+            assert!(
+                !context.is_null(),
+                "unable to create an EGL context (error 0x{:X})",
+                eglGetError()
+            );
+
+            assert!(eglMakeCurrent(display, surface, surface, context) != EGL_FALSE);
+
+            Self {
+                display,
+                surface,
+                context,
+            }
+        }
+    }
+
+    pub fn swap_buffers(&self) {
+        unsafe { eglSwapBuffers(self.display, self.surface) };
+    }
+}
+
+impl Drop for Context {
+    fn drop(&mut self) {
+        // The display is shared by every context in the process, so it is left initialized.
+        unsafe {
+            eglMakeCurrent(
+                self.display,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+            );
+            eglDestroySurface(self.display, self.surface);
+            eglDestroyContext(self.display, self.context);
+        }
+    }
+}
@@ -0,0 +1,173 @@
+#[cfg(target_os = "linux")]
+pub mod egl;
+
+#[cfg(target_os = "linux")]
+use std::path::{Path, PathBuf};
+
+#[cfg(target_os = "linux")]
+use crate::args::Args;
+#[cfg(target_os = "linux")]
+use crate::image::{self, ImageError};
+use crate::input::{Key, Modifiers};
+use crate::monitors::Monitor;
+use crate::screenshot::Screenshot;
+#[cfg(target_os = "linux")]
+use crate::zoomer::Zoomer;
+
+use super::{Platform, WindowMode};
+
+/// A platform without a window, used to drive the zoomer in tests and to render offscreen. Captures are filled in by
+/// `desktop_pixel`.
+///
+/// It has no OpenGL context unless created with [`HeadlessPlatform::with_opengl`].
+pub struct HeadlessPlatform {
+    pub client_size: (u32, u32),
+    pub monitors: Vec<Monitor>,
+    /// Returns the RGBA color of the virtual desktop pixel at the given position.
+    pub desktop_pixel: fn(i32, i32) -> [u8; 4],
+    pub cursor_position: (i32, i32),
+    pub window_mode: WindowMode,
+
+    pub is_visible: bool,
+    pub is_focused: bool,
+    pub hotkey: Option<(Modifiers, Key)>,
+    pub capture_count: u32,
+    pub swap_count: u32,
+
+    #[cfg(target_os = "linux")]
+    opengl_context: Option<egl::Context>,
+}
+
+impl HeadlessPlatform {
+    /// Creates a headless platform with a single monitor of the given size and a client area of the same size.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            client_size: (width, height),
+            monitors: vec![Monitor {
+                name: String::from("headless"),
+                x: 0,
+                y: 0,
+                width,
+                height,
+                scale_factor: 1.0,
+                is_primary: true,
+            }],
+            desktop_pixel: |_, _| [0xFF, 0x00, 0xFF, 0xFF],
+            cursor_position: (0, 0),
+            window_mode: WindowMode::Windowed,
+
+            is_visible: true,
+            is_focused: true,
+            hotkey: None,
+            capture_count: 0,
+            swap_count: 0,
+
+            #[cfg(target_os = "linux")]
+            opengl_context: None,
+        }
+    }
+
+    /// Creates a headless platform like [`HeadlessPlatform::new`] with an offscreen OpenGL context of the client
+    /// area's size, current on the calling thread.
+    #[cfg(target_os = "linux")]
+    pub fn with_opengl(width: u32, height: u32) -> Self {
+        Self {
+            opengl_context: Some(egl::Context::new(width, height)),
+            ..Self::new(width, height)
+        }
+    }
+}
+
+impl Platform for HeadlessPlatform {
+    fn show(&mut self) {
+        self.is_visible = true;
+    }
+
+    fn hide(&mut self) {
+        self.is_visible = false;
+        self.is_focused = false;
+    }
+
+    fn focus(&mut self) {
+        self.is_focused = true;
+    }
+
+    fn set_window_mode(&mut self, mode: WindowMode) {
+        if let WindowMode::Borderless { width, height, .. } = mode {
+            self.client_size = (width, height);
+        }
+
+        self.window_mode = mode;
+    }
+
+    fn swap_buffers(&mut self) {
+        #[cfg(target_os = "linux")]
+        if let Some(opengl_context) = &self.opengl_context {
+            opengl_context.swap_buffers();
+        }
+
+        self.swap_count += 1;
+    }
+
+    fn client_size(&self) -> (u32, u32) {
+        self.client_size
+    }
+
+    fn capture_screen(
+        &mut self,
+        start_x: i32,
+        start_y: i32,
+        width: u32,
+        height: u32,
+    ) -> Screenshot {
+        self.capture_count += 1;
+
+        let stride = width * Screenshot::BYTES_PER_PIXEL;
+        let pixel_bytes = (0..height as i32)
+            .flat_map(|y| (0..width as i32).map(move |x| (x, y)))
+            .flat_map(|(x, y)| (self.desktop_pixel)(start_x + x, start_y + y))
+            .collect();
+
+        Screenshot::new(width, height, pixel_bytes, stride)
+    }
+
+    fn enumerate_monitors(&self) -> Vec<Monitor> {
+        self.monitors.clone()
+    }
+
+    fn cursor_position(&self) -> (i32, i32) {
+        self.cursor_position
+    }
+
+    fn register_hotkey(&mut self, modifiers: Modifiers, key: Key) {
+        self.hotkey = Some((modifiers, key));
+    }
+
+    fn init_imgui(&mut self, _imgui: &mut imgui::Context) {}
+
+    fn new_imgui_frame(&mut self, imgui: &mut imgui::Context) {
+        let io = imgui.io_mut();
+
+        io.display_size = [self.client_size.0 as f32, self.client_size.1 as f32];
+        io.delta_time = 1.0 / 60.0;
+    }
+}
+
+/// Renders the image at `image_path` offscreen at its own size, as the zoomer first shows it but without the UI, and
+/// saves the frame as a PNG at `output_path`. Needs neither a display server nor a GPU.
+#[cfg(target_os = "linux")]
+pub fn render(args: Args, image_path: PathBuf, output_path: &Path) -> Result<(), ImageError> {
+    let image = image::load(&image_path)?;
+
+    let mut zoomer = Zoomer::new(HeadlessPlatform::with_opengl(image.width(), image.height()));
+
+    zoomer.set_image_path(Some(image_path));
+    zoomer.set_clamp_policy(args.clamp_policy);
+    zoomer.set_minimap_corner(args.minimap_corner);
+    zoomer.set_minimap_size(args.minimap_size as f32);
+    zoomer.set_background_color(args.background_color);
+    zoomer.init();
+    zoomer.init_renderer();
+
+    image::save_png(output_path, &zoomer.render_to_image())
+}
@@ -0,0 +1,63 @@
+use crate::input::{Key, Modifiers};
+use crate::monitors::Monitor;
+use crate::screenshot::Screenshot;
+
+#[cfg(windows)]
+mod windows;
+#[cfg(windows)]
+pub use self::windows::{run, FONT_PATH};
+
+#[cfg(target_os = "linux")]
+mod x11;
+#[cfg(target_os = "linux")]
+pub use self::x11::{run, FONT_PATH};
+
+#[cfg(any(test, feature = "headless"))]
+pub mod headless;
+
+/// How the zoomer window is placed on the desktop.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WindowMode {
+    /// A regular window decorated by the window manager.
+    Windowed,
+    /// A window without decorations covering exactly the given rectangle of the virtual desktop, eg. a whole monitor,
+    /// and staying above all other windows including taskbars and panels.
+    Borderless {
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+    },
+}
+
+/// Everything the zoomer needs from the operating system: a window with an OpenGL context, screen capture and a
+/// global hotkey. Each backend translates its native events into [`InputEvent`](crate::input::InputEvent)s and feeds
+/// them to [`Zoomer::handle_event`](crate::zoomer::Zoomer::handle_event).
+pub trait Platform {
+    fn show(&mut self);
+    fn hide(&mut self);
+    /// Brings the window to the foreground and gives it keyboard focus.
+    fn focus(&mut self);
+
+    /// Changes how the window is placed, the client size changes accordingly. Should be called while the window is
+    /// hidden, some window managers ignore changes to the decorations of a shown window.
+    fn set_window_mode(&mut self, mode: WindowMode);
+
+    fn swap_buffers(&mut self);
+    fn client_size(&self) -> (u32, u32);
+
+    /// Captures the given rectangle of the virtual desktop.
+    fn capture_screen(&mut self, start_x: i32, start_y: i32, width: u32, height: u32)
+        -> Screenshot;
+    fn enumerate_monitors(&self) -> Vec<Monitor>;
+    /// Position of the mouse cursor on the virtual desktop, in the same coordinates as the monitors.
+    fn cursor_position(&self) -> (i32, i32);
+
+    /// Registers a global hotkey which is delivered as [`InputEvent::Hotkey`](crate::input::InputEvent::Hotkey) even
+    /// when the window isn't focused.
+    fn register_hotkey(&mut self, modifiers: Modifiers, key: Key);
+
+    fn init_imgui(&mut self, imgui: &mut imgui::Context);
+    /// Feeds the platform's state (display size, frame time, ...) to ImGui before a new frame is started.
+    fn new_imgui_frame(&mut self, imgui: &mut imgui::Context);
+}
@@ -0,0 +1,415 @@
+mod monitors;
+mod screenshot;
+mod wgl;
+
+use std::{ffi::c_void, ptr, time::Instant};
+
+use nalgebra_glm::{vec2, Vec2};
+use winapi::{
+    ctypes::c_int,
+    shared::{
+        minwindef::{DWORD, HINSTANCE, HIWORD, LOWORD, LPARAM, LRESULT, WPARAM},
+        windef::{HDC, HWND, POINT, RECT},
+        windowsx::{GET_X_LPARAM, GET_Y_LPARAM},
+        winerror::S_OK,
+    },
+    um::{
+        libloaderapi::GetModuleHandleA,
+        shellscalingapi::{SetProcessDpiAwareness, PROCESS_PER_MONITOR_DPI_AWARE},
+        wingdi::SwapBuffers,
+        winuser::*,
+    },
+};
+
+use crate::args::Args;
+use crate::ffi::c_str_ptr;
+use crate::imgui_impl::*;
+use crate::input::{InputEvent, Key, Modifiers, MouseButton, MouseButtons};
+use crate::monitors::Monitor;
+use crate::screenshot::Screenshot;
+use crate::zoomer::Zoomer;
+
+use super::{Platform, WindowMode};
+
+pub const FONT_PATH: &str = "C:\\Windows\\Fonts\\FiraCode-Regular.ttf";
+
+const WIDTH: i32 = 1920;
+const HEIGHT: i32 = 1080;
+
+/// The Win32 backend, owning the zoomer window and its device context.
+pub struct Win32Platform {
+    handle: HWND,
+    hdc: HDC,
+}
+
+impl Win32Platform {
+    fn new(instance: HINSTANCE) -> Self {
+        let class = unsafe {
+            RegisterClassExA(&WNDCLASSEXA {
+                cbSize: std::mem::size_of::<WNDCLASSEXA>() as u32,
+                lpfnWndProc: Some(window_proc),
+                hInstance: instance,
+                lpszClassName: c_str_ptr!("ZoomerClass"),
+                hCursor: LoadCursorW(std::ptr::null_mut(), IDC_ARROW),
+                ..Default::default()
+            })
+        };
+        assert!(class != 0);
+
+        let handle = unsafe {
+            CreateWindowExA(
+                0,
+                class as usize as *const i8,
+                c_str_ptr!("Zoomer"),
+                WS_OVERLAPPEDWINDOW,
+                CW_USEDEFAULT,
+                CW_USEDEFAULT,
+                WIDTH,
+                HEIGHT,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                instance,
+                std::ptr::null_mut(),
+            )
+        };
+        assert!(!handle.is_null());
+
+        let hdc = unsafe { GetDC(handle) };
+        assert!(!hdc.is_null());
+
+        wgl::create_opengl_context(hdc);
+
+        Self { handle, hdc }
+    }
+}
+
+impl Platform for Win32Platform {
+    fn show(&mut self) {
+        unsafe { ShowWindow(self.handle, SW_SHOW) };
+    }
+
+    fn hide(&mut self) {
+        unsafe { ShowWindow(self.handle, SW_HIDE) };
+    }
+
+    fn focus(&mut self) {
+        unsafe { SetForegroundWindow(self.handle) };
+    }
+
+    fn set_window_mode(&mut self, mode: WindowMode) {
+        unsafe {
+            // Keep the window's visibility, it is part of its style.
+            let visibility = GetWindowLongPtrA(self.handle, GWL_STYLE) & WS_VISIBLE as isize;
+
+            match mode {
+                WindowMode::Windowed => {
+                    SetWindowLongPtrA(
+                        self.handle,
+                        GWL_STYLE,
+                        WS_OVERLAPPEDWINDOW as isize | visibility,
+                    );
+                    SetWindowPos(
+                        self.handle,
+                        HWND_NOTOPMOST,
+                        0,
+                        0,
+                        WIDTH,
+                        HEIGHT,
+                        SWP_FRAMECHANGED | SWP_NOMOVE | SWP_NOACTIVATE,
+                    );
+                }
+                WindowMode::Borderless {
+                    x,
+                    y,
+                    width,
+                    height,
+                } => {
+                    SetWindowLongPtrA(self.handle, GWL_STYLE, WS_POPUP as isize | visibility);
+                    SetWindowPos(
+                        self.handle,
+                        HWND_TOPMOST,
+                        x,
+                        y,
+                        width as i32,
+                        height as i32,
+                        SWP_FRAMECHANGED | SWP_NOACTIVATE,
+                    );
+                }
+            }
+        }
+    }
+
+    fn swap_buffers(&mut self) {
+        unsafe { SwapBuffers(self.hdc) };
+    }
+
+    fn client_size(&self) -> (u32, u32) {
+        let mut rect = RECT::default();
+
+        unsafe { GetClientRect(self.handle, &mut rect) };
+
+        (
+            (rect.right - rect.left) as u32,
+            (rect.bottom - rect.top) as u32,
+        )
+    }
+
+    fn capture_screen(
+        &mut self,
+        start_x: i32,
+        start_y: i32,
+        width: u32,
+        height: u32,
+    ) -> Screenshot {
+        screenshot::take_screenshot(std::ptr::null_mut(), start_x, start_y, width, height)
+    }
+
+    fn enumerate_monitors(&self) -> Vec<Monitor> {
+        monitors::enumerate()
+    }
+
+    fn cursor_position(&self) -> (i32, i32) {
+        let mut point = POINT::default();
+
+        unsafe { GetCursorPos(&mut point) };
+
+        (point.x, point.y)
+    }
+
+    fn register_hotkey(&mut self, modifiers: Modifiers, key: Key) {
+        let mut hotkey_modifiers = 0;
+
+        if modifiers.ctrl {
+            hotkey_modifiers |= MOD_CONTROL;
+        }
+        if modifiers.shift {
+            hotkey_modifiers |= MOD_SHIFT;
+        }
+        if modifiers.alt {
+            hotkey_modifiers |= MOD_ALT;
+        }
+
+        unsafe {
+            RegisterHotKey(
+                self.handle,
+                0,
+                hotkey_modifiers as u32,
+                key_to_virtual_key(key) as u32,
+            )
+        };
+    }
+
+    fn init_imgui(&mut self, _imgui: &mut imgui::Context) {
+        unsafe { ImGui_ImplWin32_Init(self.handle as *const c_void) };
+    }
+
+    fn new_imgui_frame(&mut self, _imgui: &mut imgui::Context) {
+        unsafe { ImGui_ImplWin32_NewFrame() };
+    }
+}
+
+pub fn run(args: Args) {
+    let instance = unsafe { GetModuleHandleA(std::ptr::null()) };
+    assert!(!instance.is_null());
+
+    let platform = Win32Platform::new(instance);
+
+    unsafe {
+        assert_eq!(SetProcessDpiAwareness(PROCESS_PER_MONITOR_DPI_AWARE), S_OK);
+    }
+
+    let handle = platform.handle;
+
+    let mut zoomer = Zoomer::new(platform);
+
+    zoomer.set_image_path(args.image_path);
+    zoomer.set_zoom_mode(args.zoom_mode);
+    zoomer.set_clamp_policy(args.clamp_policy);
+    zoomer.set_minimap_corner(args.minimap_corner);
+    zoomer.set_minimap_size(args.minimap_size as f32);
+    zoomer.set_export_dir(args.export_dir);
+    zoomer.set_background_color(args.background_color);
+    zoomer.init();
+    zoomer.init_renderer();
+    zoomer.init_imgui();
+
+    // Store a pointer to the zoomer object in the window so that we can access it from the `window_proc`.
+    unsafe {
+        SetWindowLongPtrA(handle, GWLP_USERDATA, ptr::addr_of_mut!(zoomer) as isize);
+    }
+
+    zoomer.platform_mut().show();
+
+    let mut message = MSG::default();
+    let mut dt_timer = Instant::now();
+
+    unsafe {
+        'main: loop {
+            while PeekMessageA(&mut message, std::ptr::null_mut(), 0, 0, PM_REMOVE) != 0 {
+                if message.message == WM_QUIT {
+                    break 'main;
+                }
+
+                TranslateMessage(&message);
+                DispatchMessageA(&message);
+            }
+
+            zoomer.render();
+            zoomer.update(dt_timer.elapsed().as_secs_f32());
+
+            dt_timer = Instant::now();
+        }
+    }
+}
+
+unsafe extern "system" fn window_proc(
+    window: HWND,
+    message: u32,
+    w_param: WPARAM,
+    l_param: LPARAM,
+) -> LRESULT {
+    use winapi::um::winuser::*;
+
+    let zoomer = GetWindowLongPtrA(window, GWLP_USERDATA) as *mut Zoomer<Win32Platform>;
+
+    if zoomer.is_null() {
+        // zoomer has not been initialized yet.
+        return DefWindowProcA(window, message, w_param, l_param);
+    }
+
+    let zoomer = &mut *zoomer;
+
+    // SetCapture() allows from when mouse is outside of the window to be captured.
+    if ImGui_ImplWin32_WndProcHandler(window, message, w_param, l_param) != 0 {
+        return 1;
+    }
+
+    let event = match message {
+        WM_SIZE => Some(InputEvent::Resize {
+            width: LOWORD(l_param as DWORD) as u32,
+            height: HIWORD(l_param as DWORD) as u32,
+        }),
+        WM_LBUTTONDOWN | WM_RBUTTONDOWN | WM_MBUTTONDOWN => Some(InputEvent::PointerDown {
+            position: l_param_to_position(l_param),
+            button: message_to_mouse_button(message),
+        }),
+        WM_LBUTTONUP | WM_RBUTTONUP | WM_MBUTTONUP => Some(InputEvent::PointerUp {
+            position: l_param_to_position(l_param),
+            button: message_to_mouse_button(message),
+        }),
+        WM_MOUSEMOVE => Some(InputEvent::PointerMove {
+            position: l_param_to_position(l_param),
+            buttons: MouseButtons {
+                left: w_param & MK_LBUTTON != 0,
+                right: w_param & MK_RBUTTON != 0,
+                middle: w_param & MK_MBUTTON != 0,
+            },
+        }),
+        WM_MOUSEWHEEL | WM_MOUSEHWHEEL => {
+            // Unlike other mouse messages, wheel messages carry screen coordinates.
+            let mut point = POINT {
+                x: GET_X_LPARAM(l_param),
+                y: GET_Y_LPARAM(l_param),
+            };
+            ScreenToClient(window, &mut point);
+
+            let notches = GET_WHEEL_DELTA_WPARAM(w_param) as f32 / WHEEL_DELTA as f32;
+
+            Some(InputEvent::Wheel {
+                position: vec2(point.x as f32, point.y as f32),
+                delta: if message == WM_MOUSEWHEEL {
+                    vec2(0.0, notches)
+                } else {
+                    vec2(notches, 0.0)
+                },
+                modifiers: current_modifiers(),
+            })
+        }
+        WM_KEYDOWN | WM_KEYUP => virtual_key_to_key(w_param as c_int).map(|key| {
+            let modifiers = current_modifiers();
+
+            if message == WM_KEYDOWN {
+                InputEvent::KeyDown { key, modifiers }
+            } else {
+                InputEvent::KeyUp { key, modifiers }
+            }
+        }),
+        WM_HOTKEY => Some(InputEvent::Hotkey),
+        WM_DESTROY => {
+            PostQuitMessage(0);
+
+            None
+        }
+        _ => return DefWindowProcA(window, message, w_param, l_param),
+    };
+
+    if let Some(event) = event {
+        zoomer.handle_event(event);
+    }
+
+    0
+}
+
+fn l_param_to_position(l_param: LPARAM) -> Vec2 {
+    vec2(GET_X_LPARAM(l_param) as f32, GET_Y_LPARAM(l_param) as f32)
+}
+
+fn message_to_mouse_button(message: u32) -> MouseButton {
+    match message {
+        WM_LBUTTONDOWN | WM_LBUTTONUP => MouseButton::Left,
+        WM_RBUTTONDOWN | WM_RBUTTONUP => MouseButton::Right,
+        _ => MouseButton::Middle,
+    }
+}
+
+fn current_modifiers() -> Modifiers {
+    // The high-order bit of the key state is set while the key is held down.
+    let is_down = |virtual_key| unsafe { GetKeyState(virtual_key) } < 0;
+
+    Modifiers {
+        ctrl: is_down(VK_CONTROL),
+        shift: is_down(VK_SHIFT),
+        alt: is_down(VK_MENU),
+    }
+}
+
+fn virtual_key_to_key(virtual_key: c_int) -> Option<Key> {
+    match virtual_key {
+        VK_ESCAPE => Some(Key::Escape),
+        VK_RETURN => Some(Key::Enter),
+        VK_SPACE => Some(Key::Space),
+        VK_TAB => Some(Key::Tab),
+        VK_BACK => Some(Key::Backspace),
+        VK_LEFT => Some(Key::Left),
+        VK_RIGHT => Some(Key::Right),
+        VK_UP => Some(Key::Up),
+        VK_DOWN => Some(Key::Down),
+        VK_OEM_PLUS | VK_ADD => Some(Key::Plus),
+        VK_OEM_MINUS | VK_SUBTRACT => Some(Key::Minus),
+        // Virtual-key codes of letters and digits are their uppercase ASCII values.
+        0x41..=0x5A => Some(Key::Letter(virtual_key as u8 as char)),
+        0x30..=0x39 => Some(Key::Digit((virtual_key - 0x30) as u8)),
+        VK_NUMPAD0..=VK_NUMPAD9 => Some(Key::Digit((virtual_key - VK_NUMPAD0) as u8)),
+        VK_F1..=VK_F24 => Some(Key::Function((virtual_key - VK_F1) as u8 + 1)),
+        _ => None,
+    }
+}
+
+fn key_to_virtual_key(key: Key) -> c_int {
+    match key {
+        Key::Escape => VK_ESCAPE,
+        Key::Enter => VK_RETURN,
+        Key::Space => VK_SPACE,
+        Key::Tab => VK_TAB,
+        Key::Backspace => VK_BACK,
+        Key::Left => VK_LEFT,
+        Key::Right => VK_RIGHT,
+        Key::Up => VK_UP,
+        Key::Down => VK_DOWN,
+        Key::Plus => VK_OEM_PLUS,
+        Key::Minus => VK_OEM_MINUS,
+        Key::Letter(letter) => letter as c_int,
+        Key::Digit(digit) => 0x30 + digit as c_int,
+        Key::Function(number) => VK_F1 + number as c_int - 1,
+    }
+}
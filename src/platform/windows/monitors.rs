@@ -0,0 +1,81 @@
+use std::{mem::size_of, ptr};
+
+use winapi::{
+    shared::{
+        minwindef::{BOOL, LPARAM, TRUE},
+        windef::{HDC, HMONITOR, LPRECT},
+        winerror::S_OK,
+    },
+    um::{
+        shellscalingapi::{GetDpiForMonitor, MDT_EFFECTIVE_DPI},
+        winuser::{EnumDisplayMonitors, GetMonitorInfoW, MONITORINFOEXW, MONITORINFOF_PRIMARY},
+    },
+};
+
+use crate::monitors::Monitor;
+
+/// DPI which corresponds to a scale factor of 1.
+const STANDARD_DPI: f32 = 96.0;
+
+unsafe extern "system" fn monitor_enum_proc(
+    monitor: HMONITOR,
+    _: HDC,
+    rect: LPRECT,
+    monitors: LPARAM,
+) -> BOOL {
+    let monitors = &mut *(monitors as *mut Vec<Monitor>);
+
+    let mut info = MONITORINFOEXW::default();
+    info.cbSize = size_of::<MONITORINFOEXW>() as u32;
+
+    let (name, is_primary) = if GetMonitorInfoW(monitor, ptr::addr_of_mut!(info).cast()) != 0 {
+        let name_length = info
+            .szDevice
+            .iter()
+            .position(|&c| c == 0)
+            .unwrap_or(info.szDevice.len());
+
+        (
+            String::from_utf16_lossy(&info.szDevice[..name_length]),
+            info.dwFlags & MONITORINFOF_PRIMARY != 0,
+        )
+    } else {
+        (String::new(), false)
+    };
+
+    let (mut dpi_x, mut dpi_y) = (0, 0);
+    let scale_factor =
+        if GetDpiForMonitor(monitor, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y) == S_OK {
+            dpi_x as f32 / STANDARD_DPI
+        } else {
+            1.0
+        };
+
+    let rect = *rect;
+    monitors.push(Monitor {
+        name,
+        x: rect.left,
+        y: rect.top,
+        width: (rect.right - rect.left) as u32,
+        height: (rect.bottom - rect.top) as u32,
+        scale_factor,
+        is_primary,
+    });
+
+    TRUE
+}
+
+pub fn enumerate() -> Vec<Monitor> {
+    let mut monitors = Vec::new();
+
+    unsafe {
+        EnumDisplayMonitors(
+            std::ptr::null_mut(),
+            std::ptr::null(),
+            Some(monitor_enum_proc),
+            ptr::addr_of_mut!(monitors) as isize,
+        );
+    }
+
+    monitors
+}
@@ -0,0 +1,75 @@
+use std::{ffi::CStr, mem::size_of};
+
+use winapi::{shared::windef::HDC, um::wingdi::*};
+
+use crate::gl::*;
+
+/// Creates an OpenGL 3.2 core profile context for the given device context and makes it current.
+pub fn create_opengl_context(hdc: HDC) {
+    // Current format probably doesn't support OpenGL, so let's create a new poggers one.
+    let format_descriptor = PIXELFORMATDESCRIPTOR {
+        nSize: size_of::<PIXELFORMATDESCRIPTOR>() as u16,
+        dwFlags: PFD_DRAW_TO_WINDOW
+            | PFD_SUPPORT_OPENGL
+            | PFD_SUPPORT_COMPOSITION
+            | PFD_DOUBLEBUFFER,
+        cColorBits: 32,
+        cAlphaBits: 8,
+        ..Default::default()
+    };
+
+    let format_index = unsafe { ChoosePixelFormat(hdc, &format_descriptor) };
+    assert!(format_index != 0);
+
+    assert!(unsafe { SetPixelFormat(hdc, format_index, &format_descriptor) } != 0);
+
+    // Create and bind a dummy OpenGL context so we can load extension functions.
+    // Reference: https://github.com/glfw/glfw/blob/4cb36872a5fe448c205d0b46f0e8c8b57530cfe0/src/wgl_context.c#L535
+    let dummy_context = unsafe {
+        let dummy_context = wglCreateContext(hdc);
+        wglMakeCurrent(hdc, dummy_context);
+
+        dummy_context
+    };
+
+    assert!(
+        is_wgl_extension_supported(hdc, "WGL_ARB_create_context_profile"),
+        "`WGL_ARB_create_context_profile` extension not supported"
+    );
+
+    #[rustfmt::skip]
+    let attribs = [
+        WGL_CONTEXT_MAJOR_VERSION_ARB, 3,
+        WGL_CONTEXT_MINOR_VERSION_ARB, 2,
+        WGL_CONTEXT_FLAGS_ARB, WGL_CONTEXT_DEBUG_BIT_ARB,
+        WGL_CONTEXT_PROFILE_MASK_ARB, WGL_CONTEXT_CORE_PROFILE_BIT_ARB,
+        0 // null-terminated
+    ];
+
+    let opengl_handle =
+        unsafe { wglCreateContextAttribsARB(hdc, std::ptr::null_mut(), attribs.as_ptr()) };
+    assert!(!opengl_handle.is_null());
+
+    // Clean up the dummy context.
+    unsafe {
+        wglMakeCurrent(hdc, std::ptr::null_mut());
+        wglDeleteContext(dummy_context);
+    }
+
+    assert!(unsafe { wglMakeCurrent(hdc, opengl_handle) } != 0);
+
+    // Enable V-Sync. It seems like this is the default, but just in case.
+    unsafe { wglSwapIntervalEXT(1) };
+}
+
+fn is_wgl_extension_supported(hdc: HDC, extension_name: &str) -> bool {
+    let extensions = unsafe {
+        let extensions = CStr::from_ptr(wglGetExtensionsStringARB(hdc))
+            .to_str()
+            .expect("non UTF8 characters in WGL extensions string");
+
+        extensions.split(' ').collect::<Vec<_>>()
+    };
+
+    extensions.contains(&extension_name)
+}
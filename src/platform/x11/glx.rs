@@ -0,0 +1,96 @@
+use std::ffi::CStr;
+
+use x11::{
+    glx::{self, arb::*},
+    xlib,
+};
+
+use crate::gl::*;
+
+/// Chooses a double buffered RGBA framebuffer configuration that can be rendered to by a window.
+pub fn choose_framebuffer_config(display: *mut xlib::Display, screen: i32) -> glx::GLXFBConfig {
+    #[rustfmt::skip]
+    let attribs = [
+        glx::GLX_X_RENDERABLE, 1,
+        glx::GLX_DRAWABLE_TYPE, glx::GLX_WINDOW_BIT,
+        glx::GLX_RENDER_TYPE, glx::GLX_RGBA_BIT,
+        glx::GLX_DOUBLEBUFFER, 1,
+        glx::GLX_RED_SIZE, 8,
+        glx::GLX_GREEN_SIZE, 8,
+        glx::GLX_BLUE_SIZE, 8,
+        glx::GLX_ALPHA_SIZE, 8,
+        0 // null-terminated
+    ];
+
+    unsafe {
+        let mut config_count = 0;
+        let configs = glx::glXChooseFBConfig(display, screen, attribs.as_ptr(), &mut config_count);
+        assert!(
+            !configs.is_null() && config_count > 0,
+            "no suitable GLX framebuffer config found"
+        );
+
+        let config = *configs;
+        xlib::XFree(configs.cast());
+
+        config
+    }
+}
+
+/// Creates an OpenGL 3.2 core profile context for the given window and makes it current.
+pub fn create_opengl_context(
+    display: *mut xlib::Display,
+    screen: i32,
+    config: glx::GLXFBConfig,
+    window: xlib::Window,
+) -> glx::GLXContext {
+    assert!(
+        is_glx_extension_supported(display, screen, "GLX_ARB_create_context_profile"),
+        "`GLX_ARB_create_context_profile` extension not supported"
+    );
+
+    #[rustfmt::skip]
+    let attribs = [
+        GLX_CONTEXT_MAJOR_VERSION_ARB, 3,
+        GLX_CONTEXT_MINOR_VERSION_ARB, 2,
+        GLX_CONTEXT_FLAGS_ARB, GLX_CONTEXT_DEBUG_BIT_ARB,
+        GLX_CONTEXT_PROFILE_MASK_ARB, GLX_CONTEXT_CORE_PROFILE_BIT_ARB,
+        0 // null-terminated
+    ];
+
+    let context = unsafe {
+        glXCreateContextAttribsARB(
+            display,
+            config,
+            std::ptr::null_mut(),
+            xlib::True,
+            attribs.as_ptr(),
+        )
+    };
+    assert!(!context.is_null());
+
+    assert!(unsafe { glx::glXMakeCurrent(display, window, context) } != 0);
+
+    // Enable V-Sync if we can, otherwise the main loop will happily spin as fast as it can.
+    if is_glx_extension_supported(display, screen, "GLX_EXT_swap_control") {
+        unsafe { glXSwapIntervalEXT(display, window, 1) };
+    }
+
+    context
+}
+
+fn is_glx_extension_supported(
+    display: *mut xlib::Display,
+    screen: i32,
+    extension_name: &str,
+) -> bool {
+    let extensions = unsafe {
+        let extensions = CStr::from_ptr(glx::glXQueryExtensionsString(display, screen))
+            .to_str()
+            .expect("non UTF8 characters in GLX extensions string");
+
+        extensions.split(' ').collect::<Vec<_>>()
+    };
+
+    extensions.contains(&extension_name)
+}
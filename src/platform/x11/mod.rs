@@ -0,0 +1,645 @@
+mod glx;
+mod monitors;
+mod screenshot;
+
+use std::{
+    mem::MaybeUninit,
+    os::raw::{c_char, c_long, c_uint},
+    ptr,
+    time::Instant,
+};
+
+use nalgebra_glm::{vec2, Vec2};
+use x11::{glx as glx_sys, keysym, xlib};
+
+use crate::args::Args;
+use crate::ffi::c_str_ptr;
+use crate::input::{InputEvent, Key, Modifiers, MouseButton, MouseButtons};
+use crate::monitors::Monitor;
+use crate::screenshot::Screenshot;
+use crate::zoomer::Zoomer;
+
+use super::{Platform, WindowMode};
+
+pub const FONT_PATH: &str = "/usr/share/fonts/truetype/firacode/FiraCode-Regular.ttf";
+
+const WIDTH: u32 = 1920;
+const HEIGHT: u32 = 1080;
+
+/// `_MOTIF_WM_HINTS` flag saying that the decorations field is set.
+const MWM_HINTS_DECORATIONS: c_long = 1 << 1;
+
+/// The X11 backend, owning the X display connection, the zoomer window and its GLX context.
+pub struct X11Platform {
+    display: *mut xlib::Display,
+    root: xlib::Window,
+    handle: xlib::Window,
+    wm_delete_window: xlib::Atom,
+    net_active_window: xlib::Atom,
+    motif_wm_hints: xlib::Atom,
+    net_wm_state: xlib::Atom,
+    net_wm_state_above: xlib::Atom,
+    /// Keycode of the key of the global hotkey.
+    hotkey_keycode: c_uint,
+    /// There is no ImGui X11 backend, so we have to keep track of the frame time ourselves.
+    last_imgui_frame: Instant,
+}
+
+impl X11Platform {
+    fn new() -> Self {
+        unsafe {
+            let display = xlib::XOpenDisplay(std::ptr::null());
+            assert!(!display.is_null(), "unable to open the X display");
+
+            let screen = xlib::XDefaultScreen(display);
+            let root = xlib::XRootWindow(display, screen);
+
+            // Otherwise every auto-repeated key press comes with a release, as if the key was let go.
+            let mut is_supported = xlib::False;
+            xlib::XkbSetDetectableAutoRepeat(display, xlib::True, &mut is_supported);
+            if is_supported == xlib::False {
+                println!("Detectable auto-repeat isn't supported, held keys will be released and pressed again.");
+            }
+
+            let framebuffer_config = glx::choose_framebuffer_config(display, screen);
+
+            let visual_info = glx_sys::glXGetVisualFromFBConfig(display, framebuffer_config);
+            assert!(!visual_info.is_null());
+
+            let mut attributes: xlib::XSetWindowAttributes = std::mem::zeroed();
+            attributes.colormap =
+                xlib::XCreateColormap(display, root, (*visual_info).visual, xlib::AllocNone);
+            attributes.event_mask = xlib::KeyPressMask
+                | xlib::KeyReleaseMask
+                | xlib::ButtonPressMask
+                | xlib::ButtonReleaseMask
+                | xlib::PointerMotionMask
+                | xlib::StructureNotifyMask;
+
+            let handle = xlib::XCreateWindow(
+                display,
+                root,
+                0,
+                0,
+                WIDTH,
+                HEIGHT,
+                0,
+                (*visual_info).depth,
+                xlib::InputOutput as c_uint,
+                (*visual_info).visual,
+                xlib::CWColormap | xlib::CWEventMask,
+                &mut attributes,
+            );
+            assert!(handle != 0);
+
+            xlib::XFree(visual_info.cast());
+
+            xlib::XStoreName(display, handle, c_str_ptr!("Zoomer"));
+
+            // Ask the window manager to send us a message instead of killing the connection when the window is closed.
+            let mut wm_delete_window =
+                xlib::XInternAtom(display, c_str_ptr!("WM_DELETE_WINDOW"), xlib::False);
+            xlib::XSetWMProtocols(display, handle, &mut wm_delete_window, 1);
+
+            let net_active_window =
+                xlib::XInternAtom(display, c_str_ptr!("_NET_ACTIVE_WINDOW"), xlib::False);
+
+            // The de facto standard way of asking window managers not to decorate a window.
+            let motif_wm_hints =
+                xlib::XInternAtom(display, c_str_ptr!("_MOTIF_WM_HINTS"), xlib::False);
+
+            let net_wm_state = xlib::XInternAtom(display, c_str_ptr!("_NET_WM_STATE"), xlib::False);
+            let net_wm_state_above =
+                xlib::XInternAtom(display, c_str_ptr!("_NET_WM_STATE_ABOVE"), xlib::False);
+
+            glx::create_opengl_context(display, screen, framebuffer_config, handle);
+
+            Self {
+                display,
+                root,
+                handle,
+                wm_delete_window,
+                net_active_window,
+                motif_wm_hints,
+                net_wm_state,
+                net_wm_state_above,
+                hotkey_keycode: 0,
+                last_imgui_frame: Instant::now(),
+            }
+        }
+    }
+
+    fn next_event(&self) -> Option<xlib::XEvent> {
+        unsafe {
+            if xlib::XPending(self.display) == 0 {
+                return None;
+            }
+
+            let mut event = MaybeUninit::uninit();
+            xlib::XNextEvent(self.display, event.as_mut_ptr());
+
+            Some(event.assume_init())
+        }
+    }
+
+    /// Translates an X event into an input event, if it is one the zoomer cares about.
+    unsafe fn translate_event(&self, event: &mut xlib::XEvent) -> Option<InputEvent> {
+        match event.get_type() {
+            xlib::ConfigureNotify => Some(InputEvent::Resize {
+                width: event.configure.width as u32,
+                height: event.configure.height as u32,
+            }),
+            event_type @ (xlib::ButtonPress | xlib::ButtonRelease) => {
+                let button = event.button;
+                let position = vec2(button.x as f32, button.y as f32);
+                let is_press = event_type == xlib::ButtonPress;
+
+                let mouse_button = match button.button {
+                    xlib::Button1 => MouseButton::Left,
+                    xlib::Button2 => MouseButton::Middle,
+                    xlib::Button3 => MouseButton::Right,
+                    // Wheel notches are reported as presses of buttons 4 to 7.
+                    _ if !is_press => return None,
+                    wheel_button => {
+                        return wheel_delta(wheel_button).map(|delta| InputEvent::Wheel {
+                            position,
+                            delta,
+                            modifiers: state_to_modifiers(button.state),
+                        })
+                    }
+                };
+
+                if is_press {
+                    Some(InputEvent::PointerDown {
+                        position,
+                        button: mouse_button,
+                    })
+                } else {
+                    Some(InputEvent::PointerUp {
+                        position,
+                        button: mouse_button,
+                    })
+                }
+            }
+            xlib::MotionNotify => {
+                let motion = event.motion;
+
+                Some(InputEvent::PointerMove {
+                    position: vec2(motion.x as f32, motion.y as f32),
+                    buttons: MouseButtons {
+                        left: motion.state & xlib::Button1Mask != 0,
+                        right: motion.state & xlib::Button3Mask != 0,
+                        middle: motion.state & xlib::Button2Mask != 0,
+                    },
+                })
+            }
+            event_type @ (xlib::KeyPress | xlib::KeyRelease) => {
+                // The hotkey is grabbed on the root window, that's the only key we get from there.
+                if event.key.window == self.root {
+                    let is_hotkey =
+                        event_type == xlib::KeyPress && event.key.keycode == self.hotkey_keycode;
+
+                    return is_hotkey.then(|| InputEvent::Hotkey);
+                }
+
+                let key = keysym_to_key(xlib::XLookupKeysym(&mut event.key, 0))?;
+                let modifiers = state_to_modifiers(event.key.state);
+
+                if event_type == xlib::KeyPress {
+                    Some(InputEvent::KeyDown { key, modifiers })
+                } else {
+                    Some(InputEvent::KeyUp { key, modifiers })
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Platform for X11Platform {
+    fn show(&mut self) {
+        unsafe {
+            xlib::XMapRaised(self.display, self.handle);
+            xlib::XFlush(self.display);
+        }
+    }
+
+    fn hide(&mut self) {
+        unsafe {
+            xlib::XUnmapWindow(self.display, self.handle);
+            xlib::XFlush(self.display);
+        }
+    }
+
+    /// Asks the window manager to activate the window. `XSetInputFocus` can't be used here as it fails if the window
+    /// hasn't been mapped yet.
+    fn focus(&mut self) {
+        unsafe {
+            let mut event: xlib::XEvent = std::mem::zeroed();
+
+            event.client_message.type_ = xlib::ClientMessage;
+            event.client_message.window = self.handle;
+            event.client_message.message_type = self.net_active_window;
+            event.client_message.format = 32;
+            // Source indication: 1 = normal application.
+            event.client_message.data.set_long(0, 1);
+            event
+                .client_message
+                .data
+                .set_long(1, xlib::CurrentTime as c_long);
+
+            xlib::XSendEvent(
+                self.display,
+                self.root,
+                xlib::False,
+                xlib::SubstructureRedirectMask | xlib::SubstructureNotifyMask,
+                &mut event,
+            );
+            xlib::XFlush(self.display);
+        }
+    }
+
+    fn set_window_mode(&mut self, mode: WindowMode) {
+        unsafe {
+            let (x, y, width, height, decorations) = match mode {
+                WindowMode::Windowed => (0, 0, WIDTH, HEIGHT, 1),
+                WindowMode::Borderless {
+                    x,
+                    y,
+                    width,
+                    height,
+                } => (x, y, width, height, 0),
+            };
+
+            // flags, functions, decorations, input mode, status
+            let motif_hints: [c_long; 5] = [MWM_HINTS_DECORATIONS, 0, decorations, 0, 0];
+
+            xlib::XChangeProperty(
+                self.display,
+                self.handle,
+                self.motif_wm_hints,
+                self.motif_wm_hints,
+                32,
+                xlib::PropModeReplace,
+                motif_hints.as_ptr().cast(),
+                motif_hints.len() as i32,
+            );
+
+            // Without user specified position and size, window managers are free to place the window elsewhere.
+            let mut size_hints: xlib::XSizeHints = std::mem::zeroed();
+            size_hints.flags = xlib::USPosition | xlib::USSize;
+            size_hints.x = x;
+            size_hints.y = y;
+            size_hints.width = width as i32;
+            size_hints.height = height as i32;
+
+            xlib::XSetWMNormalHints(self.display, self.handle, &mut size_hints);
+
+            // The window manager reads the initial state when the window is mapped, so it can be set directly.
+            if mode == WindowMode::Windowed {
+                xlib::XDeleteProperty(self.display, self.handle, self.net_wm_state);
+                xlib::XResizeWindow(self.display, self.handle, width, height);
+            } else {
+                xlib::XChangeProperty(
+                    self.display,
+                    self.handle,
+                    self.net_wm_state,
+                    xlib::XA_ATOM,
+                    32,
+                    xlib::PropModeReplace,
+                    ptr::addr_of!(self.net_wm_state_above).cast(),
+                    1,
+                );
+                xlib::XMoveResizeWindow(self.display, self.handle, x, y, width, height);
+            }
+
+            xlib::XFlush(self.display);
+        }
+    }
+
+    fn swap_buffers(&mut self) {
+        unsafe { glx_sys::glXSwapBuffers(self.display, self.handle) };
+    }
+
+    fn client_size(&self) -> (u32, u32) {
+        unsafe {
+            let mut attributes: xlib::XWindowAttributes = std::mem::zeroed();
+
+            xlib::XGetWindowAttributes(self.display, self.handle, &mut attributes);
+
+            (attributes.width as u32, attributes.height as u32)
+        }
+    }
+
+    /// Maps the keys ImGui uses for navigation and text editing to the keycodes `update_imgui_io` sets as down.
+    fn init_imgui(&mut self, imgui: &mut imgui::Context) {
+        let io = imgui.io_mut();
+
+        for (imgui_key, keysym) in [
+            (imgui::Key::Tab, keysym::XK_Tab),
+            (imgui::Key::LeftArrow, keysym::XK_Left),
+            (imgui::Key::RightArrow, keysym::XK_Right),
+            (imgui::Key::UpArrow, keysym::XK_Up),
+            (imgui::Key::DownArrow, keysym::XK_Down),
+            (imgui::Key::PageUp, keysym::XK_Page_Up),
+            (imgui::Key::PageDown, keysym::XK_Page_Down),
+            (imgui::Key::Home, keysym::XK_Home),
+            (imgui::Key::End, keysym::XK_End),
+            (imgui::Key::Insert, keysym::XK_Insert),
+            (imgui::Key::Delete, keysym::XK_Delete),
+            (imgui::Key::Backspace, keysym::XK_BackSpace),
+            (imgui::Key::Space, keysym::XK_space),
+            (imgui::Key::Enter, keysym::XK_Return),
+            (imgui::Key::Escape, keysym::XK_Escape),
+            (imgui::Key::KeyPadEnter, keysym::XK_KP_Enter),
+            (imgui::Key::A, keysym::XK_a),
+            (imgui::Key::C, keysym::XK_c),
+            (imgui::Key::V, keysym::XK_v),
+            (imgui::Key::X, keysym::XK_x),
+            (imgui::Key::Y, keysym::XK_y),
+            (imgui::Key::Z, keysym::XK_z),
+        ] {
+            io.key_map[imgui_key as usize] =
+                unsafe { xlib::XKeysymToKeycode(self.display, keysym as xlib::KeySym) } as u32;
+        }
+    }
+
+    fn new_imgui_frame(&mut self, imgui: &mut imgui::Context) {
+        let (width, height) = self.client_size();
+
+        let io = imgui.io_mut();
+        io.display_size = [width as f32, height as f32];
+        // ImGui asserts that the delta time is positive.
+        io.delta_time = self
+            .last_imgui_frame
+            .elapsed()
+            .as_secs_f32()
+            .max(f32::EPSILON);
+
+        self.last_imgui_frame = Instant::now();
+    }
+
+    fn capture_screen(
+        &mut self,
+        start_x: i32,
+        start_y: i32,
+        width: u32,
+        height: u32,
+    ) -> Screenshot {
+        screenshot::take_screenshot(self.display, start_x, start_y, width, height)
+    }
+
+    fn enumerate_monitors(&self) -> Vec<Monitor> {
+        monitors::enumerate(self.display)
+    }
+
+    fn cursor_position(&self) -> (i32, i32) {
+        unsafe {
+            let (mut root, mut child) = (0, 0);
+            let (mut root_x, mut root_y, mut window_x, mut window_y) = (0, 0, 0, 0);
+            let mut state = 0;
+
+            xlib::XQueryPointer(
+                self.display,
+                self.root,
+                &mut root,
+                &mut child,
+                &mut root_x,
+                &mut root_y,
+                &mut window_x,
+                &mut window_y,
+                &mut state,
+            );
+
+            (root_x, root_y)
+        }
+    }
+
+    /// Grabs the hotkey on the root window so we get it even when another application has focus.
+    fn register_hotkey(&mut self, modifiers: Modifiers, key: Key) {
+        unsafe {
+            self.hotkey_keycode =
+                xlib::XKeysymToKeycode(self.display, key_to_keysym(key) as xlib::KeySym) as c_uint;
+
+            let modifier_mask = modifiers_to_state(modifiers);
+
+            // Passive grabs match modifiers exactly, so also grab the combinations with Caps Lock and Num Lock on.
+            for lock_modifiers in [
+                0,
+                xlib::LockMask,
+                xlib::Mod2Mask,
+                xlib::LockMask | xlib::Mod2Mask,
+            ] {
+                xlib::XGrabKey(
+                    self.display,
+                    self.hotkey_keycode as i32,
+                    modifier_mask | lock_modifiers,
+                    self.root,
+                    xlib::True,
+                    xlib::GrabModeAsync,
+                    xlib::GrabModeAsync,
+                );
+            }
+        }
+    }
+}
+
+pub fn run(args: Args) {
+    let platform = X11Platform::new();
+
+    let mut zoomer = Zoomer::new(platform);
+
+    zoomer.set_image_path(args.image_path);
+    zoomer.set_zoom_mode(args.zoom_mode);
+    zoomer.set_clamp_policy(args.clamp_policy);
+    zoomer.set_minimap_corner(args.minimap_corner);
+    zoomer.set_minimap_size(args.minimap_size as f32);
+    zoomer.set_export_dir(args.export_dir);
+    zoomer.set_background_color(args.background_color);
+    zoomer.init();
+    zoomer.init_renderer();
+    zoomer.init_imgui();
+    zoomer.platform_mut().show();
+
+    let mut dt_timer = Instant::now();
+
+    'main: loop {
+        while let Some(mut event) = zoomer.platform().next_event() {
+            if !handle_event(&mut zoomer, &mut event) {
+                break 'main;
+            }
+        }
+
+        zoomer.render();
+        zoomer.update(dt_timer.elapsed().as_secs_f32());
+
+        dt_timer = Instant::now();
+    }
+}
+
+/// Dispatches an X event to the zoomer. Returns `false` if the application should quit.
+fn handle_event(zoomer: &mut Zoomer<X11Platform>, event: &mut xlib::XEvent) -> bool {
+    unsafe {
+        if event.get_type() == xlib::ClientMessage
+            && event.client_message.data.get_long(0) as xlib::Atom
+                == zoomer.platform().wm_delete_window
+        {
+            return false;
+        }
+
+        update_imgui_io(zoomer.imgui_io_mut(), event);
+
+        if let Some(input_event) = zoomer.platform().translate_event(event) {
+            zoomer.handle_event(input_event);
+        }
+    }
+
+    true
+}
+
+/// Feeds mouse and keyboard input to ImGui, this is what `ImGui_ImplWin32_WndProcHandler` does on Windows.
+unsafe fn update_imgui_io(io: &mut imgui::Io, event: &mut xlib::XEvent) {
+    match event.get_type() {
+        xlib::MotionNotify => {
+            io.mouse_pos = [event.motion.x as f32, event.motion.y as f32];
+        }
+        xlib::ButtonPress | xlib::ButtonRelease => {
+            let is_down = event.get_type() == xlib::ButtonPress;
+
+            match event.button.button {
+                xlib::Button1 => io.mouse_down[0] = is_down,
+                xlib::Button2 => io.mouse_down[2] = is_down,
+                xlib::Button3 => io.mouse_down[1] = is_down,
+                xlib::Button4 if is_down => io.mouse_wheel += 1.0,
+                xlib::Button5 if is_down => io.mouse_wheel -= 1.0,
+                _ => {}
+            }
+        }
+        event_type @ (xlib::KeyPress | xlib::KeyRelease) => {
+            let is_down = event_type == xlib::KeyPress;
+
+            // Keycodes are always between 8 and 255, `init_imgui` maps ImGui's keys to them.
+            if let Some(key_down) = io.keys_down.get_mut(event.key.keycode as usize) {
+                *key_down = is_down;
+            }
+
+            // The state is from before the event, without the change of the modifier key itself.
+            let mut modifiers = state_to_modifiers(event.key.state);
+            match xlib::XLookupKeysym(&mut event.key, 0) as c_uint {
+                keysym::XK_Control_L | keysym::XK_Control_R => modifiers.ctrl = is_down,
+                keysym::XK_Shift_L | keysym::XK_Shift_R => modifiers.shift = is_down,
+                keysym::XK_Alt_L | keysym::XK_Alt_R => modifiers.alt = is_down,
+                _ => {}
+            }
+
+            io.key_ctrl = modifiers.ctrl;
+            io.key_shift = modifiers.shift;
+            io.key_alt = modifiers.alt;
+
+            // Shortcuts don't type anything.
+            if !is_down || modifiers.ctrl || modifiers.alt {
+                return;
+            }
+
+            // The text is Latin-1, which maps directly to the first 256 code points.
+            let mut text: [c_char; 32] = [0; 32];
+            let length = xlib::XLookupString(
+                &mut event.key,
+                text.as_mut_ptr(),
+                text.len() as i32,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+
+            for &byte in &text[..length.max(0) as usize] {
+                let character = byte as u8 as char;
+
+                if !character.is_control() {
+                    io.add_input_character(character);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Wheel delta in notches of a wheel button press.
+fn wheel_delta(button: c_uint) -> Option<Vec2> {
+    match button {
+        xlib::Button4 => Some(vec2(0.0, 1.0)),
+        xlib::Button5 => Some(vec2(0.0, -1.0)),
+        6 => Some(vec2(-1.0, 0.0)),
+        7 => Some(vec2(1.0, 0.0)),
+        _ => None,
+    }
+}
+
+fn state_to_modifiers(state: c_uint) -> Modifiers {
+    Modifiers {
+        ctrl: state & xlib::ControlMask != 0,
+        shift: state & xlib::ShiftMask != 0,
+        alt: state & xlib::Mod1Mask != 0,
+    }
+}
+
+fn modifiers_to_state(modifiers: Modifiers) -> c_uint {
+    let mut state = 0;
+
+    if modifiers.ctrl {
+        state |= xlib::ControlMask;
+    }
+    if modifiers.shift {
+        state |= xlib::ShiftMask;
+    }
+    if modifiers.alt {
+        state |= xlib::Mod1Mask;
+    }
+
+    state
+}
+
+/// Translates an unshifted X keysym to a [`Key`].
+fn keysym_to_key(keysym: xlib::KeySym) -> Option<Key> {
+    let keysym = keysym as c_uint;
+
+    match keysym {
+        keysym::XK_Escape => Some(Key::Escape),
+        keysym::XK_Return | keysym::XK_KP_Enter => Some(Key::Enter),
+        keysym::XK_space => Some(Key::Space),
+        keysym::XK_Tab => Some(Key::Tab),
+        keysym::XK_BackSpace => Some(Key::Backspace),
+        keysym::XK_Left => Some(Key::Left),
+        keysym::XK_Right => Some(Key::Right),
+        keysym::XK_Up => Some(Key::Up),
+        keysym::XK_Down => Some(Key::Down),
+        // Plus shares the key with equals on most layouts.
+        keysym::XK_plus | keysym::XK_equal | keysym::XK_KP_Add => Some(Key::Plus),
+        keysym::XK_minus | keysym::XK_KP_Subtract => Some(Key::Minus),
+        keysym::XK_a..=keysym::XK_z => {
+            Some(Key::Letter((b'A' + (keysym - keysym::XK_a) as u8) as char))
+        }
+        keysym::XK_0..=keysym::XK_9 => Some(Key::Digit((keysym - keysym::XK_0) as u8)),
+        keysym::XK_KP_0..=keysym::XK_KP_9 => Some(Key::Digit((keysym - keysym::XK_KP_0) as u8)),
+        keysym::XK_F1..=keysym::XK_F24 => Some(Key::Function((keysym - keysym::XK_F1) as u8 + 1)),
+        _ => None,
+    }
+}
+
+fn key_to_keysym(key: Key) -> c_uint {
+    match key {
+        Key::Escape => keysym::XK_Escape,
+        Key::Enter => keysym::XK_Return,
+        Key::Space => keysym::XK_space,
+        Key::Tab => keysym::XK_Tab,
+        Key::Backspace => keysym::XK_BackSpace,
+        Key::Left => keysym::XK_Left,
+        Key::Right => keysym::XK_Right,
+        Key::Up => keysym::XK_Up,
+        Key::Down => keysym::XK_Down,
+        Key::Plus => keysym::XK_plus,
+        Key::Minus => keysym::XK_minus,
+        Key::Letter(letter) => keysym::XK_a + (letter as u8 - b'A') as c_uint,
+        Key::Digit(digit) => keysym::XK_0 + digit as c_uint,
+        Key::Function(number) => keysym::XK_F1 + number as c_uint - 1,
+    }
+}
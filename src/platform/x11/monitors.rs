@@ -0,0 +1,103 @@
+use std::ffi::CStr;
+
+use x11::{xlib, xrandr};
+
+use crate::monitors::Monitor;
+
+/// DPI which corresponds to a scale factor of 1.
+const STANDARD_DPI: f32 = 96.0;
+const MILLIMETERS_PER_INCH: f32 = 25.4;
+
+/// Enumerates the active monitors of the X screen using XRandR, falling back to the whole screen if XRandR reports none.
+pub fn enumerate(display: *mut xlib::Display) -> Vec<Monitor> {
+    unsafe {
+        let root = xlib::XDefaultRootWindow(display);
+
+        let mut monitors = Vec::new();
+
+        let (mut event_base, mut error_base) = (0, 0);
+        let has_xrandr = xrandr::XRRQueryExtension(display, &mut event_base, &mut error_base) != 0;
+
+        let mut monitor_count = 0;
+        let monitor_infos = if has_xrandr {
+            xrandr::XRRGetMonitors(display, root, xlib::True, &mut monitor_count)
+        } else {
+            std::ptr::null_mut()
+        };
+
+        if !monitor_infos.is_null() {
+            for info in std::slice::from_raw_parts(monitor_infos, monitor_count as usize) {
+                monitors.push(Monitor {
+                    name: atom_name(display, info.name),
+                    x: info.x,
+                    y: info.y,
+                    width: info.width as u32,
+                    height: info.height as u32,
+                    scale_factor: scale_factor(info.width, info.mwidth),
+                    is_primary: info.primary != 0,
+                });
+            }
+
+            xrandr::XRRFreeMonitors(monitor_infos);
+        }
+
+        if monitors.is_empty() {
+            let screen = xlib::XDefaultScreen(display);
+
+            let width = xlib::XDisplayWidth(display, screen);
+
+            monitors.push(Monitor {
+                name: String::from("screen"),
+                x: 0,
+                y: 0,
+                width: width as u32,
+                height: xlib::XDisplayHeight(display, screen) as u32,
+                scale_factor: scale_factor(width, xlib::XDisplayWidthMM(display, screen)),
+                is_primary: true,
+            });
+        }
+
+        monitors
+    }
+}
+
+unsafe fn atom_name(display: *mut xlib::Display, atom: xlib::Atom) -> String {
+    let name = xlib::XGetAtomName(display, atom);
+
+    if name.is_null() {
+        return String::new();
+    }
+
+    let owned_name = CStr::from_ptr(name).to_string_lossy().into_owned();
+    xlib::XFree(name.cast());
+
+    owned_name
+}
+
+/// X has no notion of per-monitor scaling, so derive it from the monitor's physical size. The result is rounded to a
+/// quarter as reported sizes are imprecise, and it is 1 for monitors which don't report a size (eg. projectors).
+fn scale_factor(width_pixels: i32, width_millimeters: i32) -> f32 {
+    if width_pixels <= 0 || width_millimeters <= 0 {
+        return 1.0;
+    }
+
+    let dpi = width_pixels as f32 / (width_millimeters as f32 / MILLIMETERS_PER_INCH);
+
+    ((dpi / STANDARD_DPI * 4.0).round() / 4.0).max(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::scale_factor;
+
+    #[test]
+    fn scale_factor_from_physical_size() {
+        // 24" 1080p
+        assert_eq!(scale_factor(1920, 531), 1.0);
+        // 27" 4K
+        assert_eq!(scale_factor(3840, 597), 1.75);
+        // 13" 2560x1600 laptop
+        assert_eq!(scale_factor(2560, 286), 2.25);
+        assert_eq!(scale_factor(1920, 0), 1.0);
+    }
+}
@@ -0,0 +1,162 @@
+use std::{ffi::c_void, os::raw::c_uint, ptr};
+
+use x11::{xlib, xshm};
+
+use crate::screenshot::{round_up_to_power_of_2, Screenshot};
+
+// System V shared memory, used to receive screenshots through the MIT-SHM extension.
+const IPC_PRIVATE: i32 = 0;
+const IPC_CREAT: i32 = 0o1000;
+const IPC_RMID: i32 = 0;
+
+extern "C" {
+    fn shmget(key: i32, size: usize, flags: i32) -> i32;
+    fn shmat(id: i32, address: *const c_void, flags: i32) -> *mut c_void;
+    fn shmdt(address: *const c_void) -> i32;
+    fn shmctl(id: i32, command: i32, buffer: *mut c_void) -> i32;
+}
+
+// MIT-SHM lives in libXext which the `x11` crate doesn't link by itself.
+#[link(name = "Xext")]
+extern "C" {}
+
+/// Take a screenshot of the root window constrained by the given rectangle, using MIT-SHM if the X server supports it.
+pub fn take_screenshot(
+    display: *mut xlib::Display,
+    start_x: i32,
+    start_y: i32,
+    width: u32,
+    height: u32,
+) -> Screenshot {
+    unsafe {
+        let root = xlib::XDefaultRootWindow(display);
+
+        if xshm::XShmQueryExtension(display) != 0 {
+            return take_screenshot_shm(display, root, start_x, start_y, width, height);
+        }
+
+        let image = xlib::XGetImage(
+            display,
+            root,
+            start_x,
+            start_y,
+            width,
+            height,
+            xlib::XAllPlanes(),
+            xlib::ZPixmap,
+        );
+        assert!(!image.is_null());
+
+        let screenshot = image_to_screenshot(&*image);
+
+        xlib::XDestroyImage(image);
+
+        screenshot
+    }
+}
+
+unsafe fn take_screenshot_shm(
+    display: *mut xlib::Display,
+    root: xlib::Window,
+    start_x: i32,
+    start_y: i32,
+    width: u32,
+    height: u32,
+) -> Screenshot {
+    let screen = xlib::XDefaultScreen(display);
+
+    let mut segment_info: xshm::XShmSegmentInfo = std::mem::zeroed();
+
+    let image = xshm::XShmCreateImage(
+        display,
+        xlib::XDefaultVisual(display, screen),
+        xlib::XDefaultDepth(display, screen) as u32,
+        xlib::ZPixmap,
+        ptr::null_mut(),
+        &mut segment_info,
+        width,
+        height,
+    );
+    assert!(!image.is_null());
+
+    let image_size = ((*image).bytes_per_line * (*image).height) as usize;
+
+    segment_info.shmid = shmget(IPC_PRIVATE, image_size, IPC_CREAT | 0o600);
+    assert!(segment_info.shmid != -1);
+
+    segment_info.shmaddr = shmat(segment_info.shmid, ptr::null(), 0).cast();
+    assert!(segment_info.shmaddr as isize != -1);
+
+    segment_info.readOnly = xlib::False;
+    (*image).data = segment_info.shmaddr;
+
+    assert!(xshm::XShmAttach(display, &mut segment_info) != 0);
+
+    // The `x11` crate declares the plane mask as a `c_uint`, which is still wide enough for all planes.
+    let plane_mask = xlib::XAllPlanes() as c_uint;
+    let ret = xshm::XShmGetImage(display, root, image, start_x, start_y, plane_mask);
+    assert!(ret != 0);
+
+    let screenshot = image_to_screenshot(&*image);
+
+    xshm::XShmDetach(display, &mut segment_info);
+    xlib::XSync(display, xlib::False);
+
+    // The pixel data belongs to the shared memory segment, so don't let Xlib free it.
+    (*image).data = ptr::null_mut();
+    xlib::XDestroyImage(image);
+
+    shmdt(segment_info.shmaddr.cast());
+    shmctl(segment_info.shmid, IPC_RMID, ptr::null_mut());
+
+    screenshot
+}
+
+/// Converts a 32 bits per pixel `XImage` into an RGBA screenshot.
+unsafe fn image_to_screenshot(image: &xlib::XImage) -> Screenshot {
+    assert!(
+        image.bits_per_pixel == 32,
+        "only 32 bits per pixel X images are supported"
+    );
+
+    let width = image.width as u32;
+    let height = image.height as u32;
+    let stride = round_up_to_power_of_2(width * Screenshot::BYTES_PER_PIXEL, 4);
+
+    let image_bytes = std::slice::from_raw_parts(
+        image.data as *const u8,
+        (image.bytes_per_line * image.height) as usize,
+    );
+
+    let red_shift = image.red_mask.trailing_zeros();
+    let green_shift = image.green_mask.trailing_zeros();
+    let blue_shift = image.blue_mask.trailing_zeros();
+
+    let mut pixel_bytes = vec![0u8; (stride * height) as usize];
+
+    for y in 0..height {
+        let row = &image_bytes[(y * image.bytes_per_line as u32) as usize..];
+
+        for x in 0..width {
+            let pixel_bytes_in = row[(x * 4) as usize..(x * 4 + 4) as usize]
+                .try_into()
+                .unwrap();
+
+            let pixel = if image.byte_order == xlib::LSBFirst {
+                u32::from_le_bytes(pixel_bytes_in)
+            } else {
+                u32::from_be_bytes(pixel_bytes_in)
+            };
+
+            let pixel_index = ((x + y * width) * Screenshot::BYTES_PER_PIXEL) as usize;
+
+            pixel_bytes[pixel_index] = (pixel >> red_shift) as u8;
+            pixel_bytes[pixel_index + 1] = (pixel >> green_shift) as u8;
+            pixel_bytes[pixel_index + 2] = (pixel >> blue_shift) as u8;
+            // Depth 24 visuals leave the alpha byte undefined.
+            pixel_bytes[pixel_index + 3] = 0xFF;
+        }
+    }
+
+    Screenshot::new(width, height, pixel_bytes, stride)
+}
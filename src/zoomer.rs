@@ -1,29 +1,30 @@
 use std::backtrace::Backtrace;
-use std::ffi::c_void;
+use std::f32::consts::TAU;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
 use std::{
     ffi::{CStr, CString},
     mem::{size_of, size_of_val},
 };
 use std::{fs, ptr};
 
-use crate::camera::Camera;
+use crate::bookmarks::Bookmarks;
+use crate::camera::{Camera, CameraView, ClampPolicy};
 use crate::ffi::c_str_ptr;
+use crate::gl::*;
 use crate::highlighter::Highlighter;
+use crate::image::{self, ImageError};
 use crate::imgui_impl::*;
-use crate::screenshot::take_screenshot;
+use crate::input::{InputEvent, Key, Modifiers, MouseButton, MouseButtons};
+use crate::interpolation::{Easing, InterpolatedScalar, Tween};
+use crate::minimap::{Minimap, MinimapCorner};
+use crate::monitors::{self, Monitor};
+use crate::platform::{self, Platform, WindowMode};
 use crate::{console, screenshot::Screenshot};
-use crate::{gl::*, monitors};
-
-use imgui::{Condition, FontConfig, FontSource};
-use nalgebra_glm::{vec2, vec3, vec4, Mat4, Vec2, Vec3};
-use winapi::um::winuser::{SetForegroundWindow, ShowWindow, SW_HIDE, SW_SHOW, VK_ESCAPE};
-use winapi::{
-    shared::windef::{HDC, HWND},
-    um::{
-        wingdi::*,
-        winuser::{GetDC, VK_F2},
-    },
-};
+
+use imgui::{Condition, FontConfig, FontSource, TextureId};
+use nalgebra_glm::{clamp_vec, vec2, vec3, vec4, Mat4, Vec2, Vec3};
 
 const VERTEX_SHADER: &str = r#"
 #version 330 core
@@ -78,16 +79,76 @@ void main() {
 
 const DEBUG_GL_ERROR_BACKTRACE: bool = true;
 
-pub struct Zoomer {
+/// How much the camera rotates per notch of the mouse wheel, in radians.
+const ROTATION_PER_WHEEL_NOTCH: f32 = 5.0 * std::f32::consts::PI / 180.0;
+
+/// How long in seconds the pan velocity is averaged over, so the camera coasts with the velocity right before letting
+/// go rather than that of the last few pixels.
+const PAN_VELOCITY_SMOOTHING: f32 = 0.05;
+
+/// Half of the size of the area in the middle of the screen, in screen space, in which the cursor can move without the
+/// camera following it.
+const FOLLOW_DEAD_ZONE: f32 = 0.6;
+/// How fast the camera follows the cursor at the edge of the screen, in screen space units per second.
+const MAX_FOLLOW_SPEED: f32 = 1.5;
+
+/// Box zooms smaller than this in pixels on both axes are treated as clicks.
+const MIN_BOX_ZOOM_SIZE: f32 = 4.0;
+
+/// How long it takes to fly to a bookmark, in seconds.
+const BOOKMARK_FLIGHT_DURATION: f32 = 1.0;
+
+/// How fast the arrow keys and WASD start panning, in screen space units per second.
+const KEY_PAN_SPEED: f32 = 0.5;
+/// How much faster panning with the keyboard gets per second the keys are held, in screen space units per second.
+const KEY_PAN_ACCELERATION: f32 = 1.5;
+const MAX_KEY_PAN_SPEED: f32 = 3.0;
+/// How much + and - zoom in and out per press.
+const KEY_ZOOM_STEP: f32 = 1.25;
+
+const ZOOM_RANGE: RangeInclusive<f32> = 0.25..=500.0;
+
+/// Gap between the magnification HUD and the edges of the window, in pixels.
+const HUD_MARGIN: f32 = 12.0;
+
+pub const DEFAULT_BACKGROUND_COLOR: [u8; 4] = [0x40, 0x40, 0x47, 0xFF];
+pub const DEFAULT_MINIMAP_SIZE: u32 = 240;
+
+/// What is captured and where it is shown.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ZoomMode {
+    /// The whole virtual desktop is captured and shown in a regular window.
+    Window,
+    /// The whole virtual desktop is captured, the window covers all monitors and opens at 1:1 so the zoomer looks just
+    /// like the desktop until the camera moves.
+    Overlay,
+    /// Only the monitor under the cursor is captured, the window covers that monitor and opens at 1:1.
+    Monitor,
+}
+
+pub struct Zoomer<P: Platform> {
     pub client_width: u32,
     pub client_height: u32,
 
-    window: Option<HWND>,
-    hdc: Option<HDC>,
+    platform: P,
     imgui: Option<imgui::Context>,
     screenshot: Option<Screenshot>,
+    /// Whether the screenshot has changed since it was last uploaded to the GPU.
+    screenshot_is_outdated: bool,
     /// Whether the zoomer window is currently open and showing.
     is_open: bool,
+    /// Image which is shown instead of a capture of the screen.
+    image_path: Option<PathBuf>,
+    zoom_mode: ZoomMode,
+    clamp_policy: ClampPolicy,
+    /// Monitors as of the last capture of the screen.
+    monitors: Vec<Monitor>,
+    /// RGBA color of the parts of the capture which aren't covered by any monitor and of the area around it.
+    background_color: [u8; 4],
+    /// Directory the capture and the view are exported to.
+    export_dir: PathBuf,
+    /// Whether the next rendered frame should be exported, before the UI is drawn on top of it.
+    view_export_requested: bool,
 
     vao_id: GLuint,
     texture_id: GLuint,
@@ -100,28 +161,71 @@ pub struct Zoomer {
     mouse_position_uniform: GLint,
 
     debug_window_is_open: bool,
+    /// Contents of the debug UI's image path input.
+    image_path_input: String,
+    /// Why the last image opened from the debug UI couldn't be opened.
+    open_image_error: Option<String>,
+    /// Contents of the debug UI's export directory input.
+    export_dir_input: String,
+    /// Where the last export was saved to, or why it failed.
+    export_status: Option<String>,
 
     highlighter: Highlighter,
+    bookmarks: Bookmarks,
+    minimap: Minimap,
 
     /// Current mouse position in pixel coordinate space.
     mouse_pos: Vec2,
     /// Last mouse position in screen coordinate space.
     last_mouse_screen_pos: Vec2,
+    /// Whether the camera is being dragged around with the left mouse button.
+    is_panning: bool,
+    /// How far the camera was dragged since the last update in screen space.
+    pan_since_update: Vec2,
+    /// Smoothed velocity of the drag in screen space units per second.
+    pan_velocity: Vec2,
+    /// Whether the camera keeps moving after letting go of a drag.
+    kinetic_panning: bool,
+    /// Whether the camera pans towards the cursor when it leaves the dead zone, like a magnifier.
+    follow_cursor: bool,
+    /// Where the box zoom selection started in pixel space, while one is being dragged.
+    box_zoom_start: Option<Vec2>,
+    /// Whether Z is held, which turns dragging with the left mouse button into a box zoom.
+    box_zoom_key_is_down: bool,
+    /// Whether the camera follows the cursor around the minimap while the left mouse button is held.
+    is_dragging_minimap: bool,
+    /// Whether the camera snaps to whole screen pixels per pixel of the capture once it settles, see
+    /// [`Zoomer::snap_to_pixels`].
+    pixel_snapping: bool,
+    /// Whether the magnification is shown in a corner of the window.
+    hud_is_shown: bool,
+    /// Arrow and WASD keys which are held, in the order they were pressed.
+    pan_keys_down: Vec<Key>,
+    /// Speeds up from [`KEY_PAN_SPEED`] to [`MAX_KEY_PAN_SPEED`] from when the keyboard pan starts until all keys
+    /// are let go.
+    key_pan_speed: InterpolatedScalar<f32, Tween<f32, 1>>,
 
     camera: Option<Camera>,
 }
 
-impl Zoomer {
-    pub fn new() -> Self {
+impl<P: Platform> Zoomer<P> {
+    pub fn new(platform: P) -> Self {
         Self {
             client_width: 0,
             client_height: 0,
 
-            window: None,
-            hdc: None,
+            platform,
             imgui: None,
             screenshot: None,
+            screenshot_is_outdated: false,
             is_open: false,
+            image_path: None,
+            zoom_mode: ZoomMode::Overlay,
+            clamp_policy: ClampPolicy::KeepAnyPartVisible,
+            monitors: Vec::new(),
+            background_color: DEFAULT_BACKGROUND_COLOR,
+            export_dir: PathBuf::from("."),
+            view_export_requested: false,
 
             vao_id: 0,
             texture_id: 0,
@@ -134,96 +238,205 @@ impl Zoomer {
             mouse_position_uniform: -1,
 
             debug_window_is_open: false,
+            image_path_input: String::new(),
+            open_image_error: None,
+            export_dir_input: String::from("."),
+            export_status: None,
 
             highlighter: Highlighter::new(),
+            bookmarks: Bookmarks::new(),
+            minimap: Minimap::new(),
 
             mouse_pos: Vec2::zeros(),
             last_mouse_screen_pos: Vec2::zeros(),
+            is_panning: false,
+            pan_since_update: Vec2::zeros(),
+            pan_velocity: Vec2::zeros(),
+            kinetic_panning: true,
+            follow_cursor: false,
+            box_zoom_start: None,
+            box_zoom_key_is_down: false,
+            is_dragging_minimap: false,
+            pixel_snapping: false,
+            hud_is_shown: false,
+            pan_keys_down: Vec::new(),
+            key_pan_speed: InterpolatedScalar::new(
+                KEY_PAN_SPEED,
+                Tween::new(
+                    (MAX_KEY_PAN_SPEED - KEY_PAN_SPEED) / KEY_PAN_ACCELERATION,
+                    Easing::Linear,
+                ),
+            ),
 
             camera: None,
         }
     }
 
-    pub fn init(&mut self, window: HWND, client_width: i32, client_height: i32) {
-        self.screenshot = Some(self.take_screenshot());
+    /// Takes the initial screenshot and sets up the camera. Doesn't touch OpenGL, see [`Zoomer::init_renderer`].
+    pub fn init(&mut self) {
+        let (client_width, client_height) = self.platform.client_size();
+        self.client_width = client_width;
+        self.client_height = client_height;
 
-        self.client_width = client_width as u32;
-        self.client_height = client_height as u32;
+        self.platform
+            .register_hotkey(Modifiers::ALT, Key::Letter('A'));
 
-        self.window = Some(window);
-        self.hdc = Some(unsafe { GetDC(window) });
+        // The monitors are only looked up once, so the window covers exactly what was captured.
+        let captured_monitors = self.captured_monitors();
+        let screenshot = self.capture(&captured_monitors);
+
+        self.cover_captured_area(&captured_monitors);
+        self.set_screenshot(screenshot);
 
-        self.camera = Some(Camera::new(
-            0.25..=500.0,
-            vec2(1.0, self.aspect_ratio_ratio()),
-        ));
         self.is_open = true;
+    }
 
-        self.create_opengl_context();
-        self.init_render_env();
+    /// Sets what is captured and where it is shown, must be called before [`Zoomer::init`].
+    pub fn set_zoom_mode(&mut self, zoom_mode: ZoomMode) {
+        self.zoom_mode = zoom_mode;
+    }
 
-        self.init_imgui(window);
+    pub fn set_clamp_policy(&mut self, clamp_policy: ClampPolicy) {
+        self.clamp_policy = clamp_policy;
 
-        unsafe {
-            glClearColor(0.25, 0.25, 0.28, 1.0);
+        if let Some(camera) = &mut self.camera {
+            camera.set_clamp_policy(clamp_policy);
+            camera.clamp_me_daddy();
         }
     }
 
-    fn create_opengl_context(&self) {
-        // Current format probably doesn't support OpenGL, so let's create a new poggers one.
-        let format_descriptor = PIXELFORMATDESCRIPTOR {
-            nSize: size_of::<PIXELFORMATDESCRIPTOR>() as u16,
-            dwFlags: PFD_DRAW_TO_WINDOW
-                | PFD_SUPPORT_OPENGL
-                | PFD_SUPPORT_COMPOSITION
-                | PFD_DOUBLEBUFFER,
-            cColorBits: 32,
-            cAlphaBits: 8,
-            ..Default::default()
-        };
+    /// Shows the minimap in `corner` of the window, or hides it if `None`.
+    pub fn set_minimap_corner(&mut self, corner: Option<MinimapCorner>) {
+        self.minimap.set_enabled(corner.is_some());
+
+        if let Some(corner) = corner {
+            self.minimap.set_corner(corner);
+        }
+    }
 
-        let hdc = self.hdc.unwrap();
+    /// Sets the length of the longer side of the minimap in pixels.
+    pub fn set_minimap_size(&mut self, size: f32) {
+        self.minimap.set_size(size);
+    }
 
-        let format_index = unsafe { ChoosePixelFormat(hdc, &format_descriptor) };
-        assert!(format_index != 0);
+    /// Shows the image at `image_path` instead of capturing the screen, starting with the next capture. `None` goes
+    /// back to capturing the screen.
+    pub fn set_image_path(&mut self, image_path: Option<PathBuf>) {
+        self.image_path = image_path;
+    }
 
-        assert!(unsafe { SetPixelFormat(hdc, format_index, &format_descriptor) } != 0);
+    /// Opens the image at `path` right away and keeps showing it instead of capturing the screen.
+    pub fn open_image(&mut self, path: PathBuf) -> Result<(), ImageError> {
+        let image = image::load(&path)?;
 
-        // Create and bind a dummy OpenGL context so we can load extension functions.
-        // Reference: https://github.com/glfw/glfw/blob/4cb36872a5fe448c205d0b46f0e8c8b57530cfe0/src/wgl_context.c#L535
-        let dummy_context = unsafe {
-            let dummy_context = wglCreateContext(hdc);
-            wglMakeCurrent(hdc, dummy_context);
+        self.image_path = Some(path);
+        self.set_screenshot(image);
 
-            dummy_context
-        };
+        Ok(())
+    }
 
-        assert!(
-            is_wgl_extension_supported(hdc, "WGL_ARB_create_context_profile"),
-            "`WGL_ARB_create_context_profile` extension not supported"
-        );
+    /// Sets the RGBA color of the area around the capture and of the parts of the virtual desktop which aren't covered
+    /// by any monitor, starting with the next capture.
+    pub fn set_background_color(&mut self, background_color: [u8; 4]) {
+        self.background_color = background_color;
+    }
 
-        #[rustfmt::skip]
-        let attribs = [
-            WGL_CONTEXT_MAJOR_VERSION_ARB, 3,
-            WGL_CONTEXT_MINOR_VERSION_ARB, 2,
-            WGL_CONTEXT_FLAGS_ARB, WGL_CONTEXT_DEBUG_BIT_ARB,
-            WGL_CONTEXT_PROFILE_MASK_ARB, WGL_CONTEXT_CORE_PROFILE_BIT_ARB,
-            0 // null-terminated
-        ];
+    /// Sets the directory exports are saved to, it is created on the first export if it doesn't exist.
+    pub fn set_export_dir(&mut self, export_dir: PathBuf) {
+        self.export_dir_input = export_dir.display().to_string();
+        self.export_dir = export_dir;
+    }
 
-        let opengl_handle =
-            unsafe { wglCreateContextAttribsARB(hdc, std::ptr::null_mut(), attribs.as_ptr()) };
-        assert!(!opengl_handle.is_null());
+    /// Saves the full capture (or opened image) as a PNG in the export directory, returning the file's path.
+    pub fn export_capture(&mut self) -> Result<PathBuf, ImageError> {
+        let path = self.export_path("capture")?;
 
-        // Clean up the dummy context.
-        unsafe {
-            wglMakeCurrent(hdc, std::ptr::null_mut());
-            wglDeleteContext(dummy_context);
+        image::save_png(&path, self.screenshot.as_ref().unwrap())?;
+
+        Ok(path)
+    }
+
+    /// Saves the next rendered frame, with the camera and highlighter applied but without the UI, as a PNG in the
+    /// export directory.
+    pub fn export_view(&mut self) {
+        self.view_export_requested = true;
+    }
+
+    fn export_requested_view(&mut self) {
+        if !self.view_export_requested {
+            return;
+        }
+
+        self.view_export_requested = false;
+
+        let view = self.read_framebuffer();
+        let result = self
+            .export_path("view")
+            .and_then(|path| image::save_png(&path, &view).map(|_| path));
+
+        self.set_export_status("view", result);
+    }
+
+    /// Returns a new timestamped path in the export directory, creating the directory if needed.
+    fn export_path(&self, kind: &str) -> Result<PathBuf, ImageError> {
+        fs::create_dir_all(&self.export_dir)?;
+
+        Ok(self
+            .export_dir
+            .join(export_file_name(kind, SystemTime::now())))
+    }
+
+    fn set_export_status(&mut self, kind: &str, result: Result<PathBuf, ImageError>) {
+        let status = match result {
+            Ok(path) => format!("Saved the {} to `{}`", kind, path.display()),
+            Err(error) => format!("Unable to save the {}: {}", kind, error),
+        };
+
+        println!("{}", status);
+
+        self.export_status = Some(status);
+    }
+
+    /// Loads the image at `image_path` if there is one, falling back to a capture of `captured_monitors`.
+    fn capture(&mut self, captured_monitors: &[Monitor]) -> Screenshot {
+        if let Some(image_path) = &self.image_path {
+            match image::load(image_path) {
+                Ok(image) => return image,
+                Err(error) => println!(
+                    "Unable to open `{}` ({}), capturing the screen instead.",
+                    image_path.display(),
+                    error
+                ),
+            }
         }
 
-        assert!(unsafe { wglMakeCurrent(hdc, opengl_handle) } != 0);
+        self.take_screenshot(captured_monitors)
+    }
+
+    /// Replaces the screenshot and resets the camera, as its bounds depend on the screenshot's aspect ratio.
+    fn set_screenshot(&mut self, screenshot: Screenshot) {
+        self.screenshot = Some(screenshot);
+        self.screenshot_is_outdated = true;
+
+        let mut camera = Camera::new(
+            ZOOM_RANGE,
+            vec2(1.0, self.aspect_ratio_ratio()),
+            self.client_aspect_ratio(),
+        );
+        camera.set_clamp_policy(self.clamp_policy);
+
+        self.camera = Some(camera);
+    }
+
+    /// Sets up everything needed for rendering, the platform's OpenGL context must already be current. ImGui is set up
+    /// separately by [`Zoomer::init_imgui`], without it no UI is drawn.
+    pub fn init_renderer(&mut self) {
+        self.init_opengl();
+        self.init_render_env();
+    }
 
+    /// Logs the OpenGL version and hooks up debug output for the current context.
+    fn init_opengl(&self) {
         println!("OpenGL context created!");
 
         let version = unsafe { glGetString(GL_VERSION) };
@@ -468,8 +681,6 @@ impl Zoomer {
 
         self.texture_id = texture;
 
-        self.upload_screenshot_to_gpu();
-
         unsafe {
             glEnable(GL_BLEND);
 
@@ -477,18 +688,17 @@ impl Zoomer {
         }
     }
 
-    fn init_imgui(&mut self, window: HWND) {
-        let imgui = imgui::Context::create();
+    pub fn init_imgui(&mut self) {
+        self.imgui = Some(imgui::Context::create());
+        let imgui = self.imgui.as_mut().unwrap();
+
+        self.platform.init_imgui(imgui);
 
         unsafe {
-            ImGui_ImplWin32_Init(window as *const c_void);
             ImGui_ImplOpenGL3_Init(c_str_ptr!("#version 330 core"));
         }
 
-        self.imgui = Some(imgui);
-        let imgui = self.imgui.as_mut().unwrap();
-
-        let maybe_font_data = fs::read("C:\\Windows\\Fonts\\FiraCode-Regular.ttf").ok();
+        let maybe_font_data = fs::read(platform::FONT_PATH).ok();
         let font = maybe_font_data.as_ref().map_or_else(
             || FontSource::DefaultFontData {
                 config: Some(FontConfig {
@@ -513,27 +723,17 @@ impl Zoomer {
         self.debug_window_is_open = true;
     }
 
-    fn take_screenshot(&mut self) -> Screenshot {
-        let monitors = monitors::enumerate();
-
-        assert!(!monitors.is_empty(), "no monitors found");
-
-        let (start_x, start_y) = monitors.iter().fold((0, 0), |min_start, monitor| {
-            (monitor.x.min(min_start.0), monitor.y.min(min_start.1))
-        });
-
-        let width: u32 = monitors.iter().map(|monitor| monitor.width).sum();
-        let height = monitors.iter().map(|monitor| monitor.height).max().unwrap();
+    /// Captures the bounding rectangle of `captured_monitors`, see [`Zoomer::captured_monitors`].
+    fn take_screenshot(&mut self, captured_monitors: &[Monitor]) -> Screenshot {
+        let (start_x, start_y, width, height) = monitors::bounding_rect(captured_monitors);
 
         let timer = std::time::Instant::now();
 
-        let screenshot = take_screenshot(
-            std::ptr::null_mut(),
-            start_x,
-            start_y,
-            width as u32,
-            height as u32,
-        );
+        let mut screenshot = self
+            .platform
+            .capture_screen(start_x, start_y, width, height);
+
+        self.fill_uncovered_pixels(&mut screenshot, start_x, start_y, captured_monitors);
 
         println!(
             "Screenshot taken in {} seconds",
@@ -543,8 +743,85 @@ impl Zoomer {
         screenshot
     }
 
+    /// Fills the pixels of a capture starting at (`start_x`, `start_y`) which aren't shown on any of `monitors` with
+    /// the background color, as their contents are undefined.
+    fn fill_uncovered_pixels(
+        &self,
+        screenshot: &mut Screenshot,
+        start_x: i32,
+        start_y: i32,
+        monitors: &[Monitor],
+    ) {
+        let monitors_area: u64 = monitors
+            .iter()
+            .map(|monitor| monitor.width as u64 * monitor.height as u64)
+            .sum();
+
+        // Monitors only overlap when mirrored, so every pixel is covered if their areas add up to the capture's.
+        if monitors_area == screenshot.width() as u64 * screenshot.height() as u64 {
+            return;
+        }
+
+        let (width, height, stride) =
+            (screenshot.width(), screenshot.height(), screenshot.stride());
+        let pixel_bytes = screenshot.pixel_bytes_mut();
+
+        for y in 0..height {
+            for x in 0..width {
+                let (desktop_x, desktop_y) = (start_x + x as i32, start_y + y as i32);
+
+                if monitors
+                    .iter()
+                    .any(|monitor| monitor.contains(desktop_x, desktop_y))
+                {
+                    continue;
+                }
+
+                let index = (y * stride + x * Screenshot::BYTES_PER_PIXEL) as usize;
+                pixel_bytes[index..index + Screenshot::BYTES_PER_PIXEL as usize]
+                    .copy_from_slice(&self.background_color);
+            }
+        }
+    }
+
+    /// Refreshes the list of monitors and returns the ones which are captured: just the monitor under the cursor in
+    /// [`ZoomMode::Monitor`], all of them otherwise.
+    fn captured_monitors(&mut self) -> Vec<Monitor> {
+        self.monitors = self.platform.enumerate_monitors();
+
+        match self.zoom_mode {
+            ZoomMode::Window | ZoomMode::Overlay => self.monitors.clone(),
+            ZoomMode::Monitor => {
+                let (cursor_x, cursor_y) = self.platform.cursor_position();
+
+                vec![monitors::monitor_at(&self.monitors, cursor_x, cursor_y).clone()]
+            }
+        }
+    }
+
+    /// Makes the window cover `captured_monitors` exactly, borderless and above everything else, unless in
+    /// [`ZoomMode::Window`]. The client size then matches the capture, so the camera starts out at 1:1.
+    fn cover_captured_area(&mut self, captured_monitors: &[Monitor]) {
+        if self.zoom_mode == ZoomMode::Window {
+            return;
+        }
+
+        let (x, y, width, height) = monitors::bounding_rect(captured_monitors);
+
+        self.platform.set_window_mode(WindowMode::Borderless {
+            x,
+            y,
+            width,
+            height,
+        });
+
+        // Don't wait for the resize event, the camera is fit to the client size right away.
+        self.on_resize(width, height);
+    }
+
     fn upload_screenshot_to_gpu(&mut self) {
-        let screenshot = self.screenshot.as_mut().unwrap();
+        let screenshot = self.screenshot.as_ref().unwrap();
+        self.screenshot_is_outdated = false;
 
         unsafe {
             glBindTexture(GL_TEXTURE_2D, self.texture_id);
@@ -561,20 +838,84 @@ impl Zoomer {
                 0,
                 GL_RGBA as GLenum,
                 GL_UNSIGNED_BYTE,
-                screenshot.take_pixel_bytes().as_ptr().cast(),
+                screenshot.pixel_bytes().as_ptr().cast(),
             );
 
             glBindTexture(GL_TEXTURE_2D, 0);
         }
     }
 
-    pub fn on_resize(&mut self, new_client_width: u16, new_client_height: u16) {
-        self.client_width = new_client_width as u32;
-        self.client_height = new_client_height as u32;
+    /// Dispatches an input event coming from the platform. Pointer and keyboard events are dropped while ImGui wants
+    /// to capture them.
+    pub fn handle_event(&mut self, event: InputEvent) {
+        match event {
+            InputEvent::PointerMove { position, buttons } => {
+                if self.imgui_wants_mouse_events() {
+                    return;
+                }
 
-        unsafe {
-            glViewport(0, 0, self.client_width, self.client_height);
+                self.on_mouse_move(position, buttons);
+            }
+            InputEvent::PointerDown { position, button } => {
+                if self.imgui_wants_mouse_events() {
+                    return;
+                }
+
+                self.on_mouse_down(position, button);
+            }
+            // Always let releases through so a drag that ends over an ImGui window doesn't get stuck.
+            InputEvent::PointerUp { position, button } => self.on_mouse_up(position, button),
+            InputEvent::Wheel {
+                position,
+                delta,
+                modifiers,
+            } => {
+                if self.imgui_wants_mouse_events() {
+                    return;
+                }
+
+                self.on_mouse_wheel(position, delta, modifiers);
+            }
+            InputEvent::KeyDown { key, modifiers } => {
+                if self.imgui_wants_keyboard_events() {
+                    return;
+                }
+
+                self.on_key_down(key, modifiers);
+            }
+            // Like releasing the mouse, releasing a key has to get through.
+            InputEvent::KeyUp { key, .. } => self.on_key_up(key),
+            InputEvent::Hotkey => self.on_hotkey(),
+            InputEvent::Resize { width, height } => self.on_resize(width, height),
+        }
+    }
+
+    fn on_resize(&mut self, new_client_width: u32, new_client_height: u32) {
+        self.client_width = new_client_width;
+        self.client_height = new_client_height;
+
+        let client_aspect_ratio = self.client_aspect_ratio();
+
+        if let Some(camera) = &mut self.camera {
+            camera.set_aspect_ratio(client_aspect_ratio);
+        }
+
+        // The image's bounds in world space depend on the client's aspect ratio too.
+        self.update_position_range();
+    }
+
+    /// Fits the camera's position range to the current screenshot and client size, and pulls the camera back inside
+    /// it.
+    fn update_position_range(&mut self) {
+        if self.camera.is_none() {
+            return;
         }
+
+        let position_range = vec2(1.0, self.aspect_ratio_ratio());
+        let camera = self.camera.as_mut().unwrap();
+
+        camera.set_position_range(position_range);
+        camera.clamp_me_daddy();
     }
 
     /// Converts from screen pixel space ([0, `client_width`] x [0, `client_height`]) to normalized screen coordinates or NDC ([-1, 1] x [-1, 1])
@@ -585,6 +926,13 @@ impl Zoomer {
         )
     }
 
+    /// The inverse of the last step of [`Zoomer::pixel_to_uv_space`], from UV space to world space.
+    fn uv_to_world_space(&self, uv_coords: Vec2) -> Vec2 {
+        let world_coords = uv_coords * 2.0 - vec2(1.0, 1.0);
+
+        vec2(world_coords.x, -world_coords.y * self.aspect_ratio_ratio())
+    }
+
     pub fn pixel_to_uv_space(&self, pixel_coords: Vec2) -> Vec2 {
         let mut mouse_uv_pos = self
             .camera
@@ -599,181 +947,616 @@ impl Zoomer {
         mouse_uv_pos
     }
 
-    pub fn on_left_mouse_down(&mut self, x: i32, y: i32) {
-        self.last_mouse_screen_pos = self.pixel_to_screen_space(vec2(x as f32, y as f32));
+    fn on_mouse_down(&mut self, position: Vec2, button: MouseButton) {
+        match button {
+            // One drag at a time, the other button is ignored until it's over.
+            MouseButton::Left if self.box_zoom_start.is_some() => {}
+            MouseButton::Right if self.is_panning || self.is_dragging_minimap => {}
+            MouseButton::Left
+                if self.minimap.contains(
+                    position,
+                    self.client_size(),
+                    self.screenshot_aspect_ratio(),
+                ) =>
+            {
+                self.is_dragging_minimap = true;
+
+                self.center_on_minimap(position);
+            }
+            MouseButton::Left if self.box_zoom_key_is_down => self.box_zoom_start = Some(position),
+            MouseButton::Left => {
+                self.last_mouse_screen_pos = self.pixel_to_screen_space(position);
+                self.is_panning = true;
+                self.pan_since_update = Vec2::zeros();
+                self.pan_velocity = Vec2::zeros();
+
+                self.camera.as_mut().unwrap().stop();
+            }
+            MouseButton::Right => self.box_zoom_start = Some(position),
+            MouseButton::Middle => {}
+        }
+    }
+
+    fn on_mouse_up(&mut self, position: Vec2, button: MouseButton) {
+        if button == MouseButton::Left && self.is_dragging_minimap {
+            self.is_dragging_minimap = false;
+        } else if let Some(start) = self.box_zoom_start {
+            if button == MouseButton::Left || button == MouseButton::Right {
+                self.box_zoom_start = None;
+
+                self.box_zoom(start, position);
+            }
+        } else if button == MouseButton::Left && self.is_panning {
+            self.is_panning = false;
+
+            let camera = self.camera.as_mut().unwrap();
+
+            if self.kinetic_panning {
+                camera.fling(self.pan_velocity);
+            } else {
+                camera.clamp_me_daddy();
+            }
+        }
     }
 
-    pub fn on_left_mouse_up(&mut self) {
-        self.camera.as_mut().unwrap().clamp_me_daddy();
+    /// Zooms in on the rectangle between two pixel space corners.
+    fn box_zoom(&mut self, corner: Vec2, opposite_corner: Vec2) {
+        let size = (opposite_corner - corner).abs();
+
+        if size.max() < MIN_BOX_ZOOM_SIZE {
+            return;
+        }
+
+        let corner = self.pixel_to_screen_space(corner);
+        let opposite_corner = self.pixel_to_screen_space(opposite_corner);
+
+        self.camera
+            .as_mut()
+            .unwrap()
+            .zoom_to_rect(corner, opposite_corner);
     }
 
-    pub fn on_mouse_move(&mut self, x: i32, y: i32, left_mouse_down: bool) {
-        self.mouse_pos = vec2(x as f32, y as f32);
+    fn on_mouse_move(&mut self, position: Vec2, buttons: MouseButtons) {
+        self.mouse_pos = position;
 
-        if !left_mouse_down {
+        if self.is_dragging_minimap {
+            if buttons.left {
+                self.center_on_minimap(position);
+            }
+
+            return;
+        }
+
+        if !buttons.left || self.box_zoom_start.is_some() {
             return;
         }
 
         let mouse_screen_pos = self.pixel_to_screen_space(self.mouse_pos);
         let delta = mouse_screen_pos - self.last_mouse_screen_pos;
 
-        self.camera.as_mut().unwrap().translate(delta);
+        self.camera.as_mut().unwrap().drag(delta);
 
         self.last_mouse_screen_pos = mouse_screen_pos;
+        self.pan_since_update += delta;
+    }
+
+    /// Smoothly moves the camera so the point of the capture under `position` on the minimap ends up at the center of
+    /// the screen.
+    fn center_on_minimap(&mut self, position: Vec2) {
+        let uv = self.minimap.pixel_to_uv_space(
+            position,
+            self.client_size(),
+            self.screenshot_aspect_ratio(),
+        );
+        let center = self.uv_to_world_space(clamp_vec(&uv, &vec2(0.0, 0.0), &vec2(1.0, 1.0)));
+
+        let camera = self.camera.as_mut().unwrap();
+        camera.animate_to_view(CameraView {
+            center,
+            ..camera.view()
+        });
+        camera.clamp_me_daddy();
     }
 
-    pub fn on_mouse_wheel(&mut self, delta: i16, x: i32, y: i32, ctrl_is_down: bool) {
-        let delta = delta as f32 / 120.0 / 10.0;
+    fn on_mouse_wheel(&mut self, position: Vec2, delta: Vec2, modifiers: Modifiers) {
+        if modifiers.shift {
+            let screen_point = self.pixel_to_screen_space(position);
+
+            self.camera
+                .as_mut()
+                .unwrap()
+                .rotate_around(delta.y * ROTATION_PER_WHEEL_NOTCH, screen_point);
+
+            return;
+        }
+
+        let delta = delta.y / 10.0;
 
-        if ctrl_is_down && self.highlighter.is_enabled() {
+        if modifiers.ctrl && self.highlighter.is_enabled() {
             self.highlighter
                 .set_radius(self.highlighter.radius() * (1.0 + delta * 2.0));
 
             return;
         }
 
-        let screen_point = self.pixel_to_screen_space(vec2(x as f32, y as f32));
+        let screen_point = self.pixel_to_screen_space(position);
 
         let camera = self.camera.as_mut().unwrap();
 
         camera.zoom(1.0 + delta, screen_point);
     }
 
-    pub fn on_key_down(&mut self, key: u8) {
-        if key == VK_F2 as u8 {
-            self.debug_window_is_open = !self.debug_window_is_open;
-        }
+    fn on_key_down(&mut self, key: Key, modifiers: Modifiers) {
+        match key {
+            Key::Function(2) => self.debug_window_is_open = !self.debug_window_is_open,
+            Key::Letter('S') if modifiers.ctrl && modifiers.shift => {
+                let result = self.export_capture();
 
-        if key == b'C' {
-            self.highlighter.set_enabled(!self.highlighter.is_enabled());
+                self.set_export_status("capture", result);
+            }
+            Key::Letter('S') if modifiers.ctrl => self.export_view(),
+            Key::Letter('C') => self.highlighter.set_enabled(!self.highlighter.is_enabled()),
+            Key::Letter('Z') if modifiers.ctrl && modifiers.shift => {
+                self.camera.as_mut().unwrap().redo()
+            }
+            Key::Letter('Z') if modifiers.ctrl => self.camera.as_mut().unwrap().undo(),
+            Key::Letter('Y') if modifiers.ctrl => self.camera.as_mut().unwrap().redo(),
+            Key::Letter('Z') => self.box_zoom_key_is_down = true,
+            Key::Letter('F') => self.follow_cursor = !self.follow_cursor,
+            Key::Letter('M') => self.minimap.set_enabled(!self.minimap.is_enabled()),
+            Key::Letter('P') => self.pixel_snapping = !self.pixel_snapping,
+            Key::Letter('H') => self.hud_is_shown = !self.hud_is_shown,
+            Key::Left | Key::Right | Key::Up | Key::Down | Key::Letter('A' | 'D' | 'W' | 'S')
+                if !modifiers.ctrl && !self.pan_keys_down.contains(&key) =>
+            {
+                self.pan_keys_down.push(key)
+            }
+            Key::Plus | Key::Minus => {
+                let zoom_multiplier = if key == Key::Plus {
+                    KEY_ZOOM_STEP
+                } else {
+                    1.0 / KEY_ZOOM_STEP
+                };
+                // Around the center of the view, unless Ctrl is held.
+                let screen_point = if modifiers.ctrl {
+                    self.pixel_to_screen_space(self.mouse_pos)
+                } else {
+                    vec2(0.0, 0.0)
+                };
+
+                self.camera
+                    .as_mut()
+                    .unwrap()
+                    .zoom(zoom_multiplier, screen_point);
+            }
+            Key::Digit(0) => self.zoom_to_fit(),
+            // Plain 1 recalls the first bookmark.
+            Key::Digit(1) if modifiers.shift => self.zoom_to_actual_pixels(),
+            Key::Digit(slot @ 1..=9) if modifiers.ctrl => {
+                let view = self.camera.as_ref().unwrap().view();
 
-            unsafe {
-                glUseProgram(self.shader_program_id);
-                glUniform1i(
-                    self.highlighter_on_uniform,
-                    self.highlighter.is_enabled() as i32,
-                );
-                glUseProgram(0);
+                self.bookmarks.save(slot as usize, view);
             }
-        }
+            Key::Digit(slot @ 1..=9) => self.recall_bookmark(slot as usize),
+            Key::Escape => {
+                self.is_open = false;
 
-        if key == VK_ESCAPE as u8 {
-            self.is_open = false;
+                self.platform.hide();
+            }
+            _ => {}
+        }
+    }
 
-            unsafe { ShowWindow(self.window.unwrap(), SW_HIDE) };
+    fn on_key_up(&mut self, key: Key) {
+        if key == Key::Letter('Z') {
+            self.box_zoom_key_is_down = false;
         }
+
+        self.pan_keys_down.retain(|&pan_key| pan_key != key);
     }
 
-    pub fn on_hotkey(&mut self) {
-        if self.is_open {
+    /// Returns the direction the held arrow and WASD keys pan in, in screen space.
+    fn key_pan_direction(&self) -> Vec2 {
+        self.pan_keys_down
+            .iter()
+            .map(|key| match key {
+                Key::Left | Key::Letter('A') => vec2(-1.0, 0.0),
+                Key::Right | Key::Letter('D') => vec2(1.0, 0.0),
+                Key::Up | Key::Letter('W') => vec2(0.0, 1.0),
+                _ => vec2(0.0, -1.0),
+            })
+            .sum()
+    }
+
+    /// Pans the camera in the direction of the held arrow and WASD keys, faster the longer they are held.
+    fn pan_with_keys(&mut self, dt: f32) {
+        // The pan ends here rather than when the keys are released, so a release immediately followed by a press,
+        // which is how X11 auto-repeats keys, doesn't start it over.
+        if self.pan_keys_down.is_empty() {
+            if self.key_pan_speed.target() > KEY_PAN_SPEED {
+                self.key_pan_speed.set_target(KEY_PAN_SPEED);
+                self.key_pan_speed.set_current(KEY_PAN_SPEED);
+
+                self.camera.as_mut().unwrap().clamp_me_daddy();
+            }
+
             return;
         }
 
-        self.screenshot = Some(self.take_screenshot());
-        self.upload_screenshot_to_gpu();
+        let direction = self.key_pan_direction();
 
-        let window = self.window.unwrap();
+        if direction == Vec2::zeros() {
+            return;
+        }
 
-        unsafe {
-            ShowWindow(window, SW_SHOW);
-            // NOTE: This is not strictly required, but just in case.
-            SetForegroundWindow(window);
+        if self.key_pan_speed.target() < MAX_KEY_PAN_SPEED {
+            self.key_pan_speed.set_target(MAX_KEY_PAN_SPEED);
         }
 
-        self.is_open = true;
+        self.key_pan_speed.update(dt);
+        let speed = self.key_pan_speed.current();
+
+        // Moves the view, so the capture moves the other way.
+        self.camera
+            .as_mut()
+            .unwrap()
+            .translate(-direction.normalize() * speed * dt);
     }
 
-    pub fn screenshot_aspect_ratio(&self) -> f32 {
-        let screenshot = self.screenshot.as_ref().unwrap();
+    /// Smoothly resets the rotation and zooms out so the whole capture fits on the screen.
+    fn zoom_to_fit(&mut self) {
+        // The capture is as wide as the screen at a zoom factor of 1, too tall if it's relatively taller.
+        let zoom_factor = (1.0 / self.aspect_ratio_ratio()).min(1.0);
 
-        screenshot.width() as f32 / screenshot.height() as f32
+        self.camera.as_mut().unwrap().animate_to_view(CameraView {
+            center: Vec2::zeros(),
+            zoom_factor,
+            rotation: 0.0,
+        });
     }
 
-    /// Returns the ratio of the client aspect ratio to the screenshot aspect ratio
-    pub fn aspect_ratio_ratio(&self) -> f32 {
-        let client_aspect_ratio = self.client_width as f32 / self.client_height as f32;
-        let screenshot_aspect_ratio = self.screenshot_aspect_ratio();
+    /// Smoothly zooms around the center of the screen so one pixel of the capture covers one pixel of the screen.
+    fn zoom_to_actual_pixels(&mut self) {
+        let zoom_factor = self.actual_pixels_zoom_factor();
 
-        client_aspect_ratio / screenshot_aspect_ratio
+        let camera = self.camera.as_mut().unwrap();
+        camera.animate_to_view(CameraView {
+            zoom_factor,
+            ..camera.view()
+        });
     }
 
-    pub fn update(&mut self, dt: f32) {
-        self.camera.as_mut().unwrap().update(dt);
-        self.highlighter.update(dt);
-
-        let mouse_uv_pos = self.pixel_to_uv_space(self.mouse_pos);
+    /// Returns the zoom factor at which one pixel of the capture covers one pixel of the screen.
+    fn actual_pixels_zoom_factor(&self) -> f32 {
+        // The capture is as wide as the screen at a zoom factor of 1.
+        self.screenshot.as_ref().unwrap().width() as f32 / self.client_width as f32
+    }
 
-        unsafe {
-            glUseProgram(self.shader_program_id);
-            glUniform2fv(
-                self.mouse_position_uniform,
-                1,
-                vec4(mouse_uv_pos.x, mouse_uv_pos.y, 0.0, 1.0).as_ptr(),
-            );
-            glUseProgram(0);
-        }
+    /// Returns how many pixels of the screen one pixel of the capture currently covers.
+    fn magnification(&self) -> f32 {
+        self.camera.as_ref().unwrap().zoom_factor() / self.actual_pixels_zoom_factor()
+    }
 
-        let radius_uv =
-            vec2(self.highlighter.radius(), self.highlighter.radius()).component_mul(&vec2(
-                1.0 / self.client_width as f32,
-                1.0 / self.client_height as f32,
-            ));
+    /// Once the camera settles, smoothly zooms to the closest whole number of screen pixels per pixel of the capture
+    /// and lines the capture's pixels up with the screen's, so they all come out the same size. Leaves the camera
+    /// alone while it's rotated or zoomed out past 1:1, where that isn't possible.
+    fn snap_to_pixels(&mut self) {
+        let actual_pixels_zoom_factor = self.actual_pixels_zoom_factor();
+        let capture_top_left = vec2(-1.0, self.aspect_ratio_ratio());
+        let client_size = self.client_size();
 
-        let highlighter_radius_uv = vec2(radius_uv.x, radius_uv.y / self.aspect_ratio_ratio());
+        let camera = self.camera.as_mut().unwrap();
+        let view = camera.view();
+        let turn = view.rotation.rem_euclid(TAU);
 
-        unsafe {
-            glUseProgram(self.shader_program_id);
-            glUniform2fv(
-                self.highlighter_radius_uniform,
-                1,
-                highlighter_radius_uv.as_ptr(),
-            );
-            glUseProgram(0);
+        if !camera.is_settled() || turn.min(TAU - turn) > 1e-3 {
+            return;
         }
-    }
-
-    pub fn render(&mut self) {
-        let view_matrix = self.camera.as_ref().unwrap().to_homogenous()
-            * Mat4::new_nonuniform_scaling(&vec3(1.0, self.aspect_ratio_ratio(), 1.0));
 
-        unsafe {
-            glClear(GL_COLOR_BUFFER_BIT);
+        let magnification = (view.zoom_factor / actual_pixels_zoom_factor)
+            .round()
+            .min((ZOOM_RANGE.end() / actual_pixels_zoom_factor).floor());
 
-            glActiveTexture(GL_TEXTURE0);
-            glBindTexture(GL_TEXTURE_2D, self.texture_id);
-            glUseProgram(self.shader_program_id);
+        if magnification < 1.0 {
+            return;
+        }
 
-            {
-                glUniformMatrix4fv(self.view_matrix_uniform, 1, false, view_matrix.as_ptr());
+        let zoom_factor = magnification * actual_pixels_zoom_factor;
 
-                glBindVertexArray(self.vao_id);
-                glBindBuffer(GL_ELEMENT_ARRAY_BUFFER, self.index_buffer_id);
-                {
-                    glDrawElements(GL_TRIANGLES, 6, GL_UNSIGNED_BYTE, std::ptr::null());
-                }
-            }
+        // Where the top left corner of the capture ends up in pixel space once zoomed around the center of the screen,
+        // and how far it has to move to land on a pixel boundary.
+        let corner = (capture_top_left - view.center) * zoom_factor;
+        let corner = vec2(corner.x + 1.0, 1.0 - corner.y).component_mul(&client_size) / 2.0;
+        let offset = corner.map(f32::round) - corner;
 
-            glUseProgram(0);
-            glBindVertexArray(0);
-            glBindTexture(GL_TEXTURE_2D, 0);
+        if offset.abs().max() < 1e-2 && (zoom_factor / view.zoom_factor - 1.0).abs() < 1e-4 {
+            return;
         }
 
-        self.render_imgui();
+        let translation = vec2(offset.x, -offset.y).component_div(&client_size) * 2.0;
 
-        unsafe {
-            SwapBuffers(self.hdc.unwrap());
-        }
+        camera.animate_to_view(CameraView {
+            center: view.center - translation / zoom_factor,
+            zoom_factor,
+            ..view
+        });
     }
 
-    pub fn render_imgui(&mut self) {
-        unsafe {
-            ImGui_ImplOpenGL3_NewFrame();
-            ImGui_ImplWin32_NewFrame();
+    fn recall_bookmark(&mut self, slot: usize) {
+        if let Some(bookmark) = self.bookmarks.get(slot) {
+            self.camera
+                .as_mut()
+                .unwrap()
+                .fly_to_view(bookmark.view, BOOKMARK_FLIGHT_DURATION);
         }
+    }
+
+    fn on_hotkey(&mut self) {
+        if self.is_open {
+            return;
+        }
+
+        let captured_monitors = self.captured_monitors();
+        let screenshot = self.capture(&captured_monitors);
+
+        if self.zoom_mode == ZoomMode::Window {
+            // Keeps the view, but the capture's aspect ratio may have changed along with the monitors.
+            self.screenshot = Some(screenshot);
+            self.screenshot_is_outdated = true;
+            self.update_position_range();
+        } else {
+            // Start over at 1:1 so opening is seamless, the monitors may also have changed since last time.
+            self.cover_captured_area(&captured_monitors);
+            self.set_screenshot(screenshot);
+        }
+
+        self.platform.show();
+        // NOTE: This is not strictly required, but just in case.
+        self.platform.focus();
+
+        self.is_open = true;
+    }
+
+    pub fn screenshot_aspect_ratio(&self) -> f32 {
+        let screenshot = self.screenshot.as_ref().unwrap();
+
+        screenshot.width() as f32 / screenshot.height() as f32
+    }
+
+    fn client_size(&self) -> Vec2 {
+        vec2(self.client_width as f32, self.client_height as f32)
+    }
+
+    pub fn client_aspect_ratio(&self) -> f32 {
+        self.client_width as f32 / self.client_height as f32
+    }
+
+    /// Returns the ratio of the client aspect ratio to the screenshot aspect ratio
+    pub fn aspect_ratio_ratio(&self) -> f32 {
+        self.client_aspect_ratio() / self.screenshot_aspect_ratio()
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        if self.is_panning && dt > 0.0 {
+            let smoothing = 1.0 - (-dt / PAN_VELOCITY_SMOOTHING).exp();
+
+            self.pan_velocity += (self.pan_since_update / dt - self.pan_velocity) * smoothing;
+            self.pan_since_update = Vec2::zeros();
+        }
+
+        if self.follow_cursor && !self.is_panning && self.box_zoom_start.is_none() {
+            self.follow(dt);
+        }
+
+        self.pan_with_keys(dt);
+
+        self.camera.as_mut().unwrap().update(dt);
+
+        // Only views the camera comes to rest at after a gesture are snapped and go into the undo history.
+        if !self.is_panning
+            && !self.is_dragging_minimap
+            && self.box_zoom_start.is_none()
+            && self.pan_keys_down.is_empty()
+        {
+            if self.pixel_snapping {
+                self.snap_to_pixels();
+            }
+
+            self.camera.as_mut().unwrap().record_view();
+        }
+
+        self.highlighter.update(dt);
+    }
+
+    /// Pans the camera towards the cursor if it's out of the dead zone, faster the closer it is to the edge. Waits
+    /// for flights and flings to finish, as panning would cancel them.
+    fn follow(&mut self, dt: f32) {
+        if self.camera.as_ref().unwrap().is_flying_or_coasting() {
+            return;
+        }
+
+        let cursor = self.pixel_to_screen_space(self.mouse_pos);
+        let dead_zone = vec2(FOLLOW_DEAD_ZONE, FOLLOW_DEAD_ZONE);
+        let out_of_dead_zone = cursor - clamp_vec(&cursor, &-dead_zone, &dead_zone);
+
+        if out_of_dead_zone == Vec2::zeros() {
+            return;
+        }
+
+        let velocity = out_of_dead_zone / (1.0 - FOLLOW_DEAD_ZONE) * MAX_FOLLOW_SPEED;
+
+        let camera = self.camera.as_mut().unwrap();
+        camera.translate(-velocity * dt);
+        camera.clamp_me_daddy();
+    }
+
+    pub fn render(&mut self) {
+        self.draw_scene();
+        self.export_requested_view();
+
+        if self.imgui.is_some() {
+            self.render_imgui();
+        }
+
+        self.platform.swap_buffers();
+    }
+
+    /// Renders a frame like [`Zoomer::render`] and reads it back as a top-down RGBA image of the client area, eg.
+    /// offscreen through [`HeadlessPlatform`](crate::platform::headless::HeadlessPlatform).
+    #[cfg(any(test, feature = "headless"))]
+    pub fn render_to_image(&mut self) -> Screenshot {
+        self.draw_scene();
+
+        if self.imgui.is_some() {
+            self.render_imgui();
+        }
+
+        let image = self.read_framebuffer();
+
+        self.platform.swap_buffers();
+
+        image
+    }
+
+    /// Draws the capture through the camera and the highlighter, without the UI.
+    fn draw_scene(&mut self) {
+        if self.screenshot_is_outdated {
+            self.upload_screenshot_to_gpu();
+        }
+
+        let mouse_uv_pos = self.pixel_to_uv_space(self.mouse_pos);
+
+        unsafe {
+            glUseProgram(self.shader_program_id);
+            glUniform2fv(
+                self.mouse_position_uniform,
+                1,
+                vec4(mouse_uv_pos.x, mouse_uv_pos.y, 0.0, 1.0).as_ptr(),
+            );
+            glUseProgram(0);
+        }
+
+        let radius_uv =
+            vec2(self.highlighter.radius(), self.highlighter.radius()).component_mul(&vec2(
+                1.0 / self.client_width as f32,
+                1.0 / self.client_height as f32,
+            ));
+
+        let highlighter_radius_uv = vec2(radius_uv.x, radius_uv.y / self.aspect_ratio_ratio());
+
+        unsafe {
+            glUseProgram(self.shader_program_id);
+            glUniform2fv(
+                self.highlighter_radius_uniform,
+                1,
+                highlighter_radius_uv.as_ptr(),
+            );
+            glUniform1i(
+                self.highlighter_on_uniform,
+                self.highlighter.is_enabled() as i32,
+            );
+            glUseProgram(0);
+        }
+
+        let view_matrix = self.camera.as_ref().unwrap().to_homogenous()
+            * Mat4::new_nonuniform_scaling(&vec3(1.0, self.aspect_ratio_ratio(), 1.0));
+
+        let [red, green, blue, alpha] = self.background_color.map(|channel| channel as f32 / 255.0);
+
+        unsafe {
+            glViewport(0, 0, self.client_width, self.client_height);
+            glClearColor(red, green, blue, alpha);
+            glClear(GL_COLOR_BUFFER_BIT);
+
+            glActiveTexture(GL_TEXTURE0);
+            glBindTexture(GL_TEXTURE_2D, self.texture_id);
+            glUseProgram(self.shader_program_id);
+
+            {
+                glUniformMatrix4fv(self.view_matrix_uniform, 1, false, view_matrix.as_ptr());
+
+                glBindVertexArray(self.vao_id);
+                glBindBuffer(GL_ELEMENT_ARRAY_BUFFER, self.index_buffer_id);
+                {
+                    glDrawElements(GL_TRIANGLES, 6, GL_UNSIGNED_BYTE, std::ptr::null());
+                }
+            }
+
+            glUseProgram(0);
+            glBindVertexArray(0);
+            glBindTexture(GL_TEXTURE_2D, 0);
+        }
+    }
+
+    fn read_framebuffer(&self) -> Screenshot {
+        let stride = self.client_width * Screenshot::BYTES_PER_PIXEL;
+        let mut pixel_bytes = vec![0u8; (stride * self.client_height) as usize];
+
+        unsafe {
+            glPixelStorei(GL_PACK_ALIGNMENT, 1);
+            glReadPixels(
+                0,
+                0,
+                self.client_width,
+                self.client_height,
+                GL_RGBA as GLenum,
+                GL_UNSIGNED_BYTE,
+                pixel_bytes.as_mut_ptr().cast(),
+            );
+        }
+
+        // OpenGL's origin is the bottom-left corner, flip the rows so the image is top-down like a capture.
+        let pixel_bytes = pixel_bytes
+            .chunks_exact(stride as usize)
+            .rev()
+            .flatten()
+            .copied()
+            .collect();
+
+        Screenshot::new(self.client_width, self.client_height, pixel_bytes, stride)
+    }
+
+    pub fn render_imgui(&mut self) {
+        unsafe {
+            ImGui_ImplOpenGL3_NewFrame();
+        }
+
+        self.platform.new_imgui_frame(self.imgui.as_mut().unwrap());
 
         let screen_space = self.pixel_to_screen_space(self.mouse_pos);
         let uv_space = self.pixel_to_uv_space(self.mouse_pos);
 
+        let client_size = self.client_size();
+        let capture_aspect_ratio = self.screenshot_aspect_ratio();
+        let minimap_rect = self.minimap.rect(client_size, capture_aspect_ratio);
+        // The corners of the screen, which aren't a rectangle along the capture's axes if the camera is rotated.
+        let minimap_view = [
+            vec2(0.0, 0.0),
+            vec2(client_size.x, 0.0),
+            client_size,
+            vec2(0.0, client_size.y),
+        ]
+        .map(|pixel| {
+            self.minimap.uv_to_pixel_space(
+                self.pixel_to_uv_space(pixel),
+                client_size,
+                capture_aspect_ratio,
+            )
+        });
+        let minimap_corners = [
+            MinimapCorner::TopLeft,
+            MinimapCorner::TopRight,
+            MinimapCorner::BottomLeft,
+            MinimapCorner::BottomRight,
+        ];
+        let mut minimap_is_enabled = self.minimap.is_enabled();
+        let mut minimap_corner_index = minimap_corners
+            .iter()
+            .position(|&corner| corner == self.minimap.corner())
+            .unwrap();
+        let mut minimap_size = self.minimap.size();
+        let magnification = self.magnification();
+
         let camera = self.camera.as_mut().unwrap();
 
         let camera_space = camera.screen_to_camera_space(screen_space);
@@ -782,6 +1565,21 @@ impl Zoomer {
         let imgui = self.imgui.as_mut().unwrap();
         let ui = imgui.frame();
 
+        let mut open_image_clicked = false;
+        let mut save_capture_clicked = false;
+        let mut save_view_clicked = false;
+        let mut recalled_bookmark = None;
+        let clamp_policies = [
+            ClampPolicy::None,
+            ClampPolicy::KeepAnyPartVisible,
+            ClampPolicy::KeepViewInsideImage,
+        ];
+        let mut clamp_policy_index = clamp_policies
+            .iter()
+            .position(|&clamp_policy| clamp_policy == self.clamp_policy)
+            .unwrap();
+        let mut deleted_bookmark = None;
+
         if self.debug_window_is_open {
             ui.window("Debug")
                 .size([650.0, 0.0], Condition::FirstUseEver)
@@ -815,25 +1613,278 @@ impl Zoomer {
                         camera.position().x,
                         camera.position().y
                     ));
+                    ui.text(format!(
+                        "Camera rotation = {:.2} degrees",
+                        camera.rotation().to_degrees()
+                    ));
+                    ui.checkbox("Kinetic panning", &mut self.kinetic_panning);
+                    ui.checkbox("Follow the cursor", &mut self.follow_cursor);
+                    ui.combo_simple_string(
+                        "Clamp",
+                        &mut clamp_policy_index,
+                        &["None", "Keep any part visible", "Keep view inside image"],
+                    );
+                    ui.checkbox("Minimap", &mut minimap_is_enabled);
+                    ui.combo_simple_string(
+                        "Minimap corner",
+                        &mut minimap_corner_index,
+                        &["Top left", "Top right", "Bottom left", "Bottom right"],
+                    );
+                    ui.slider("Minimap size", 80.0, 600.0, &mut minimap_size);
+                    ui.checkbox("Snap to pixels", &mut self.pixel_snapping);
+                    ui.checkbox("Show the magnification", &mut self.hud_is_shown);
+
+                    ui.separator();
+
+                    for monitor in &self.monitors {
+                        ui.text(format!(
+                            "{}{}: {}x{} at ({}, {}), scale {}",
+                            monitor.name,
+                            if monitor.is_primary { " (primary)" } else { "" },
+                            monitor.width,
+                            monitor.height,
+                            monitor.x,
+                            monitor.y,
+                            monitor.scale_factor
+                        ));
+                    }
+
+                    ui.separator();
+
+                    ui.input_text("##image_path", &mut self.image_path_input)
+                        .hint("Path to a PNG, BMP or PNM image")
+                        .build();
+                    ui.same_line();
+                    open_image_clicked = ui.button("Open");
+
+                    if let Some(error) = &self.open_image_error {
+                        ui.text(error);
+                    }
+
+                    ui.separator();
+
+                    ui.input_text("##export_dir", &mut self.export_dir_input)
+                        .hint("Export directory")
+                        .build();
+                    save_capture_clicked = ui.button("Save capture");
+                    ui.same_line();
+                    save_view_clicked = ui.button("Save view");
+
+                    if let Some(status) = &self.export_status {
+                        ui.text(status);
+                    }
+
+                    ui.separator();
+
+                    if self.bookmarks.is_empty() {
+                        ui.text("No bookmarks, save one with Ctrl + 1 to 9");
+                    }
+
+                    for (slot, bookmark) in self.bookmarks.iter_mut() {
+                        ui.text(format!("{}:", slot));
+                        ui.same_line();
+                        ui.input_text(format!("##bookmark_{}", slot), &mut bookmark.name)
+                            .build();
+                        ui.same_line();
+                        if ui.button(format!("Go##bookmark_{}", slot)) {
+                            recalled_bookmark = Some(slot);
+                        }
+                        ui.same_line();
+                        if ui.button(format!("Delete##bookmark_{}", slot)) {
+                            deleted_bookmark = Some(slot);
+                        }
+                    }
                 });
         }
 
+        if let Some(start) = self.box_zoom_start {
+            let draw_list = ui.get_foreground_draw_list();
+            let (start, end) = ([start.x, start.y], [self.mouse_pos.x, self.mouse_pos.y]);
+
+            draw_list
+                .add_rect(start, end, [1.0, 1.0, 1.0, 0.15])
+                .filled(true)
+                .build();
+            draw_list
+                .add_rect(start, end, [1.0, 1.0, 1.0, 0.9])
+                .thickness(1.5)
+                .build();
+        }
+
+        if self.minimap.is_enabled() {
+            // Behind ImGui's windows, but on top of the capture.
+            let draw_list = ui.get_background_draw_list();
+            let (top_left, bottom_right) = (
+                [minimap_rect.0.x, minimap_rect.0.y],
+                [minimap_rect.1.x, minimap_rect.1.y],
+            );
+
+            draw_list
+                .add_image(
+                    TextureId::new(self.texture_id as usize),
+                    top_left,
+                    bottom_right,
+                )
+                .build();
+            draw_list.with_clip_rect_intersect(top_left, bottom_right, || {
+                for (index, corner) in minimap_view.iter().enumerate() {
+                    let next_corner = minimap_view[(index + 1) % minimap_view.len()];
+
+                    draw_list
+                        .add_line(
+                            [corner.x, corner.y],
+                            [next_corner.x, next_corner.y],
+                            [1.0, 0.85, 0.2, 1.0],
+                        )
+                        .thickness(2.0)
+                        .build();
+                }
+            });
+            draw_list
+                .add_rect(top_left, bottom_right, [1.0, 1.0, 1.0, 0.9])
+                .thickness(1.5)
+                .build();
+        }
+
+        if self.hud_is_shown {
+            let draw_list = ui.get_background_draw_list();
+            let text = format!("{:.0}%", magnification * 100.0);
+            let text_size = ui.calc_text_size(&text);
+            let position = [client_size.x - HUD_MARGIN - text_size[0], HUD_MARGIN];
+            let padding = 4.0;
+
+            draw_list
+                .add_rect(
+                    [position[0] - padding, position[1] - padding],
+                    [
+                        position[0] + text_size[0] + padding,
+                        position[1] + text_size[1] + padding,
+                    ],
+                    [0.0, 0.0, 0.0, 0.6],
+                )
+                .filled(true)
+                .build();
+            draw_list.add_text(position, [1.0, 1.0, 1.0, 1.0], text);
+        }
+
         let draw_data = imgui.render();
 
         unsafe {
             ImGui_ImplOpenGL3_RenderDrawData(draw_data as *const _ as *mut _);
         }
+
+        if open_image_clicked {
+            let path = PathBuf::from(self.image_path_input.trim());
+
+            self.open_image_error = self
+                .open_image(path)
+                .err()
+                .map(|error| format!("Unable to open the image: {}", error));
+        }
+
+        if save_capture_clicked || save_view_clicked {
+            self.export_dir = PathBuf::from(self.export_dir_input.trim());
+        }
+
+        if save_capture_clicked {
+            let result = self.export_capture();
+
+            self.set_export_status("capture", result);
+        }
+
+        if save_view_clicked {
+            self.export_view();
+        }
+
+        if clamp_policies[clamp_policy_index] != self.clamp_policy {
+            self.set_clamp_policy(clamp_policies[clamp_policy_index]);
+        }
+
+        self.minimap.set_enabled(minimap_is_enabled);
+        self.minimap
+            .set_corner(minimap_corners[minimap_corner_index]);
+        self.minimap.set_size(minimap_size);
+
+        if let Some(slot) = recalled_bookmark {
+            self.recall_bookmark(slot);
+        }
+
+        if let Some(slot) = deleted_bookmark {
+            self.bookmarks.remove(slot);
+        }
     }
 
     /// Whether ImGui wants to receive mouse events instead of the application (ie. mouse is over an ImGui window)
-    pub fn imgui_wants_mouse_events(&self) -> bool {
-        self.imgui.as_ref().unwrap().io().want_capture_mouse
+    fn imgui_wants_mouse_events(&self) -> bool {
+        match &self.imgui {
+            Some(imgui) => imgui.io().want_capture_mouse,
+            None => false,
+        }
     }
 
     /// Whether ImGui wants to receive keyboard events instead of the application
-    pub fn imgui_wants_keyboard_events(&self) -> bool {
-        self.imgui.as_ref().unwrap().io().want_capture_keyboard
+    fn imgui_wants_keyboard_events(&self) -> bool {
+        match &self.imgui {
+            Some(imgui) => imgui.io().want_capture_keyboard,
+            None => false,
+        }
     }
+
+    /// ImGui's IO state, for platforms without an ImGui backend to feed input into.
+    #[cfg_attr(windows, allow(dead_code))]
+    pub fn imgui_io_mut(&mut self) -> &mut imgui::Io {
+        self.imgui.as_mut().unwrap().io_mut()
+    }
+
+    #[cfg_attr(windows, allow(dead_code))]
+    pub fn platform(&self) -> &P {
+        &self.platform
+    }
+
+    pub fn platform_mut(&mut self) -> &mut P {
+        &mut self.platform
+    }
+}
+
+/// Names an export `zoomer-<kind>-<UTC date>_<UTC time>.png`, with milliseconds so quick exports don't collide.
+fn export_file_name(kind: &str, time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+
+    let seconds = since_epoch.as_secs();
+    let (year, month, day) = civil_from_days((seconds / 86_400) as i64);
+    let seconds_of_day = seconds % 86_400;
+
+    format!(
+        "zoomer-{}-{:04}-{:02}-{:02}_{:02}-{:02}-{:02}-{:03}.png",
+        kind,
+        year,
+        month,
+        day,
+        seconds_of_day / 3600,
+        seconds_of_day / 60 % 60,
+        seconds_of_day % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+/// Converts days since 1970-01-01 to a proleptic Gregorian (year, month, day).
+// Source: http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    } as u32;
+
+    (year_of_era + era * 400 + (month <= 2) as i64, month, day)
 }
 
 unsafe extern "C" fn gl_message_callback(
@@ -880,14 +1931,1219 @@ unsafe extern "C" fn gl_message_callback(
     );
 }
 
-fn is_wgl_extension_supported(hdc: HDC, extension_name: &str) -> bool {
-    let extensions = unsafe {
-        let extensions = CStr::from_ptr(wglGetExtensionsStringARB(hdc))
-            .to_str()
-            .expect("non UTF8 characters in WGL extensions string");
+#[cfg(test)]
+mod tests {
+    use nalgebra_glm::vec2;
+
+    use crate::input::{InputEvent, Key, Modifiers, MouseButton, MouseButtons};
+    use crate::platform::headless::HeadlessPlatform;
+
+    use super::*;
+
+    /// Long enough for every interpolation to settle.
+    const SETTLE_TIME: f32 = 10.0;
+
+    fn zoomer() -> Zoomer<HeadlessPlatform> {
+        let mut zoomer = Zoomer::new(HeadlessPlatform::new(800, 600));
+        zoomer.init();
+
+        zoomer
+    }
+
+    fn assert_vec2_eq(a: Vec2, b: Vec2) {
+        assert!((a - b).abs().max() < 1e-4, "{} != {}", a, b);
+    }
+
+    fn key_down(key: Key, modifiers: Modifiers) -> InputEvent {
+        InputEvent::KeyDown { key, modifiers }
+    }
+
+    fn wheel(position: Vec2, notches: f32, modifiers: Modifiers) -> InputEvent {
+        InputEvent::Wheel {
+            position,
+            delta: vec2(0.0, notches),
+            modifiers,
+        }
+    }
+
+    #[test]
+    fn init_registers_alt_a_hotkey() {
+        let zoomer = zoomer();
+
+        assert_eq!(
+            zoomer.platform.hotkey,
+            Some((Modifiers::ALT, Key::Letter('A')))
+        );
+    }
+
+    #[test]
+    fn escape_hides_and_hotkey_recaptures() {
+        let mut zoomer = zoomer();
+        assert_eq!(zoomer.platform.capture_count, 1);
+
+        zoomer.handle_event(key_down(Key::Escape, Modifiers::NONE));
+        assert!(!zoomer.is_open);
+        assert!(!zoomer.platform.is_visible);
+
+        zoomer.handle_event(InputEvent::Hotkey);
+        assert!(zoomer.is_open);
+        assert!(zoomer.platform.is_visible && zoomer.platform.is_focused);
+        assert_eq!(zoomer.platform.capture_count, 2);
+        assert!(zoomer.screenshot_is_outdated);
+    }
+
+    #[test]
+    fn hotkey_is_ignored_while_open() {
+        let mut zoomer = zoomer();
+
+        zoomer.handle_event(InputEvent::Hotkey);
+
+        assert_eq!(zoomer.platform.capture_count, 1);
+    }
+
+    #[test]
+    fn zoom_keeps_point_under_cursor() {
+        let mut zoomer = zoomer();
+        let cursor = vec2(600.0, 150.0);
+        let uv_before = zoomer.pixel_to_uv_space(cursor);
+
+        zoomer.handle_event(wheel(cursor, 1.0, Modifiers::NONE));
+        zoomer.update(SETTLE_TIME);
 
-        extensions.split(' ').collect::<Vec<_>>()
-    };
+        assert!((zoomer.camera.as_ref().unwrap().zoom_factor() - 1.1).abs() < 1e-4);
+        assert_vec2_eq(zoomer.pixel_to_uv_space(cursor), uv_before);
+    }
 
-    extensions.contains(&extension_name)
+    #[test]
+    fn digits_save_and_recall_bookmarks() {
+        let mut zoomer = zoomer();
+        let cursor = vec2(600.0, 150.0);
+
+        zoomer.handle_event(wheel(cursor, 5.0, Modifiers::NONE));
+        zoomer.handle_event(key_down(
+            Key::Digit(2),
+            Modifiers {
+                ctrl: true,
+                ..Modifiers::NONE
+            },
+        ));
+        zoomer.update(SETTLE_TIME);
+        let uv_center = zoomer.pixel_to_uv_space(vec2(400.0, 300.0));
+
+        zoomer.handle_event(wheel(vec2(100.0, 500.0), -3.0, Modifiers::NONE));
+        zoomer.update(SETTLE_TIME);
+        // An empty slot leaves the camera alone.
+        zoomer.handle_event(key_down(Key::Digit(1), Modifiers::NONE));
+        zoomer.update(SETTLE_TIME);
+        assert!((zoomer.camera.as_ref().unwrap().zoom_factor() - 1.05).abs() < 1e-4);
+
+        zoomer.handle_event(key_down(Key::Digit(2), Modifiers::NONE));
+        zoomer.update(SETTLE_TIME);
+
+        assert!((zoomer.camera.as_ref().unwrap().zoom_factor() - 1.5).abs() < 1e-4);
+        assert_vec2_eq(zoomer.pixel_to_uv_space(vec2(400.0, 300.0)), uv_center);
+    }
+
+    #[test]
+    fn right_drag_zooms_to_the_selection() {
+        let mut zoomer = zoomer();
+        let uv_center = zoomer.pixel_to_uv_space(vec2(500.0, 200.0));
+
+        zoomer.handle_event(InputEvent::PointerDown {
+            position: vec2(400.0, 150.0),
+            button: MouseButton::Right,
+        });
+        zoomer.handle_event(InputEvent::PointerMove {
+            position: vec2(600.0, 250.0),
+            buttons: MouseButtons {
+                right: true,
+                ..MouseButtons::default()
+            },
+        });
+        zoomer.handle_event(InputEvent::PointerUp {
+            position: vec2(600.0, 250.0),
+            button: MouseButton::Right,
+        });
+        zoomer.update(SETTLE_TIME);
+
+        // 200x100 pixels in an 800x600 window, the width is what limits it.
+        assert!((zoomer.camera.as_ref().unwrap().zoom_factor() - 4.0).abs() < 1e-3);
+        assert_vec2_eq(zoomer.pixel_to_uv_space(vec2(400.0, 300.0)), uv_center);
+    }
+
+    #[test]
+    fn z_turns_left_drag_into_box_zoom() {
+        let mut zoomer = zoomer();
+        let left = MouseButtons {
+            left: true,
+            ..MouseButtons::default()
+        };
+
+        zoomer.handle_event(key_down(Key::Letter('Z'), Modifiers::NONE));
+        zoomer.handle_event(InputEvent::PointerDown {
+            position: vec2(100.0, 100.0),
+            button: MouseButton::Left,
+        });
+        zoomer.handle_event(InputEvent::PointerMove {
+            position: vec2(300.0, 400.0),
+            buttons: left,
+        });
+        zoomer.handle_event(InputEvent::PointerUp {
+            position: vec2(300.0, 400.0),
+            button: MouseButton::Left,
+        });
+        zoomer.update(SETTLE_TIME);
+
+        // 200x300 pixels, limited by the height.
+        assert!((zoomer.camera.as_ref().unwrap().zoom_factor() - 2.0).abs() < 1e-3);
+
+        // Without Z, dragging pans again and a click doesn't zoom.
+        zoomer.handle_event(InputEvent::KeyUp {
+            key: Key::Letter('Z'),
+            modifiers: Modifiers::NONE,
+        });
+        let uv_before = zoomer.pixel_to_uv_space(vec2(100.0, 100.0));
+        zoomer.handle_event(InputEvent::PointerDown {
+            position: vec2(100.0, 100.0),
+            button: MouseButton::Left,
+        });
+        zoomer.handle_event(InputEvent::PointerMove {
+            position: vec2(150.0, 100.0),
+            buttons: left,
+        });
+        zoomer.update(SETTLE_TIME);
+
+        assert_vec2_eq(zoomer.pixel_to_uv_space(vec2(150.0, 100.0)), uv_before);
+        assert!((zoomer.camera.as_ref().unwrap().zoom_factor() - 2.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn pressing_the_other_button_mid_drag_is_ignored() {
+        let mut zoomer = zoomer();
+        let uv_before = zoomer.pixel_to_uv_space(vec2(100.0, 100.0));
+        let both = MouseButtons {
+            left: true,
+            right: true,
+            ..MouseButtons::default()
+        };
+
+        zoomer.handle_event(InputEvent::PointerDown {
+            position: vec2(100.0, 100.0),
+            button: MouseButton::Left,
+        });
+        zoomer.handle_event(InputEvent::PointerDown {
+            position: vec2(100.0, 100.0),
+            button: MouseButton::Right,
+        });
+        zoomer.handle_event(InputEvent::PointerMove {
+            position: vec2(300.0, 200.0),
+            buttons: both,
+        });
+        zoomer.handle_event(InputEvent::PointerUp {
+            position: vec2(300.0, 200.0),
+            button: MouseButton::Left,
+        });
+        zoomer.handle_event(InputEvent::PointerUp {
+            position: vec2(300.0, 200.0),
+            button: MouseButton::Right,
+        });
+        zoomer.update(SETTLE_TIME);
+
+        // It kept panning without zooming, and letting go ended the pan.
+        assert!(!zoomer.is_panning);
+        assert!(zoomer.box_zoom_start.is_none());
+        assert!((zoomer.camera.as_ref().unwrap().zoom_factor() - 1.0).abs() < 1e-4);
+        assert_vec2_eq(zoomer.pixel_to_uv_space(vec2(300.0, 200.0)), uv_before);
+
+        // The other way around, the box zoom goes on.
+        zoomer.handle_event(InputEvent::PointerDown {
+            position: vec2(400.0, 150.0),
+            button: MouseButton::Right,
+        });
+        zoomer.handle_event(InputEvent::PointerDown {
+            position: vec2(400.0, 150.0),
+            button: MouseButton::Left,
+        });
+        zoomer.handle_event(InputEvent::PointerMove {
+            position: vec2(600.0, 250.0),
+            buttons: both,
+        });
+        zoomer.handle_event(InputEvent::PointerUp {
+            position: vec2(600.0, 250.0),
+            button: MouseButton::Left,
+        });
+        zoomer.handle_event(InputEvent::PointerUp {
+            position: vec2(600.0, 250.0),
+            button: MouseButton::Right,
+        });
+        zoomer.update(SETTLE_TIME);
+
+        assert!(!zoomer.is_panning);
+        assert!((zoomer.camera.as_ref().unwrap().zoom_factor() - 4.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn drag_pans_the_camera() {
+        let mut zoomer = zoomer();
+        let uv_before = zoomer.pixel_to_uv_space(vec2(100.0, 100.0));
+
+        zoomer.handle_event(InputEvent::PointerDown {
+            position: vec2(100.0, 100.0),
+            button: MouseButton::Left,
+        });
+        zoomer.handle_event(InputEvent::PointerMove {
+            position: vec2(250.0, 175.0),
+            buttons: MouseButtons {
+                left: true,
+                ..Default::default()
+            },
+        });
+        zoomer.update(SETTLE_TIME);
+
+        assert_vec2_eq(zoomer.pixel_to_uv_space(vec2(250.0, 175.0)), uv_before);
+    }
+
+    /// Drags from `start` by `step` pixels per frame at 60 FPS, then waits for `pause` seconds before letting go.
+    fn drag(
+        zoomer: &mut Zoomer<HeadlessPlatform>,
+        start: Vec2,
+        step: Vec2,
+        frames: usize,
+        pause: f32,
+    ) {
+        let buttons = MouseButtons {
+            left: true,
+            ..Default::default()
+        };
+        let mut position = start;
+
+        zoomer.handle_event(InputEvent::PointerDown {
+            position,
+            button: MouseButton::Left,
+        });
+        for _ in 0..frames {
+            position += step;
+            zoomer.handle_event(InputEvent::PointerMove { position, buttons });
+            zoomer.update(1.0 / 60.0);
+        }
+        zoomer.update(pause);
+        zoomer.handle_event(InputEvent::PointerUp {
+            position,
+            button: MouseButton::Left,
+        });
+    }
+
+    #[test]
+    fn letting_go_of_a_drag_keeps_the_camera_moving() {
+        let mut zoomer = zoomer();
+        let uv_before = zoomer.pixel_to_uv_space(vec2(100.0, 300.0));
+
+        drag(&mut zoomer, vec2(100.0, 300.0), vec2(5.0, 0.0), 20, 0.0);
+        zoomer.update(SETTLE_TIME);
+
+        // Coasts at most velocity / friction, 300 pixels per second for 1/5 of a second.
+        let released_at = vec2(200.0, 300.0);
+        assert!(zoomer.pixel_to_uv_space(released_at).x < uv_before.x - 0.01);
+        assert!(zoomer.pixel_to_uv_space(released_at + vec2(60.0, 0.0)).x >= uv_before.x - 1e-4);
+        assert_vec2_eq(
+            vec2(zoomer.pixel_to_uv_space(released_at).y, 0.0),
+            vec2(uv_before.y, 0.0),
+        );
+    }
+
+    #[test]
+    fn drag_without_momentum_stops_dead() {
+        let mut zoomer = zoomer();
+        let uv_before = zoomer.pixel_to_uv_space(vec2(100.0, 300.0));
+
+        // Holding still before letting go.
+        drag(&mut zoomer, vec2(100.0, 300.0), vec2(5.0, 0.0), 20, 0.25);
+        zoomer.update(SETTLE_TIME);
+        assert_vec2_eq(zoomer.pixel_to_uv_space(vec2(200.0, 300.0)), uv_before);
+
+        // With kinetic panning disabled.
+        zoomer.kinetic_panning = false;
+        let uv_before = zoomer.pixel_to_uv_space(vec2(200.0, 300.0));
+
+        drag(&mut zoomer, vec2(200.0, 300.0), vec2(5.0, 0.0), 20, 0.0);
+        zoomer.update(SETTLE_TIME);
+        assert_vec2_eq(zoomer.pixel_to_uv_space(vec2(300.0, 300.0)), uv_before);
+    }
+
+    #[test]
+    fn f_makes_the_camera_follow_the_cursor() {
+        let mut zoomer = zoomer();
+        zoomer.handle_event(wheel(vec2(400.0, 300.0), 30.0, Modifiers::NONE));
+        zoomer.update(SETTLE_TIME);
+        zoomer.handle_event(key_down(Key::Letter('F'), Modifiers::NONE));
+
+        // Within the dead zone, nothing moves.
+        let uv_center = zoomer.pixel_to_uv_space(vec2(400.0, 300.0));
+        zoomer.handle_event(InputEvent::PointerMove {
+            position: vec2(600.0, 400.0),
+            buttons: MouseButtons::default(),
+        });
+        for _ in 0..60 {
+            zoomer.update(1.0 / 60.0);
+        }
+        assert_vec2_eq(zoomer.pixel_to_uv_space(vec2(400.0, 300.0)), uv_center);
+
+        // At the right edge, the camera pans right until it reaches the clamp bounds.
+        zoomer.handle_event(InputEvent::PointerMove {
+            position: vec2(799.0, 300.0),
+            buttons: MouseButtons::default(),
+        });
+        zoomer.update(0.5);
+        let uv_after_a_while = zoomer.pixel_to_uv_space(vec2(400.0, 300.0));
+        assert!(uv_after_a_while.x > uv_center.x + 0.05);
+        assert!((uv_after_a_while.y - uv_center.y).abs() < 1e-4);
+
+        for _ in 0..100 {
+            zoomer.update(0.1);
+        }
+        zoomer.update(SETTLE_TIME);
+        // Zoomed in 4 times, so at most 1/8 of the capture's width past its edge.
+        assert!((zoomer.pixel_to_uv_space(vec2(400.0, 300.0)).x - 1.125).abs() < 1e-3);
+    }
+
+    #[test]
+    fn following_the_cursor_lets_flights_and_flings_finish() {
+        let mut zoomer = zoomer();
+        zoomer.handle_event(wheel(vec2(400.0, 300.0), 30.0, Modifiers::NONE));
+        zoomer.update(SETTLE_TIME);
+        zoomer.handle_event(key_down(Key::Letter('F'), Modifiers::NONE));
+
+        // Ends the drag at the right edge, out of the dead zone.
+        drag(&mut zoomer, vec2(650.0, 300.0), vec2(5.0, 0.0), 20, 0.0);
+        for _ in 0..5 {
+            zoomer.update(1.0 / 60.0);
+            assert!(zoomer.camera.as_ref().unwrap().is_flying_or_coasting());
+        }
+        zoomer.update(SETTLE_TIME);
+
+        zoomer.handle_event(key_down(
+            Key::Digit(1),
+            Modifiers {
+                ctrl: true,
+                ..Modifiers::NONE
+            },
+        ));
+        zoomer.handle_event(wheel(vec2(400.0, 300.0), -20.0, Modifiers::NONE));
+        zoomer.update(SETTLE_TIME);
+        zoomer.handle_event(key_down(Key::Digit(1), Modifiers::NONE));
+        for _ in 0..30 {
+            zoomer.update(1.0 / 60.0);
+            assert!(zoomer.camera.as_ref().unwrap().is_flying_or_coasting());
+        }
+    }
+
+    #[test]
+    fn held_arrow_keys_pan_faster_and_faster() {
+        let mut zoomer = zoomer();
+        zoomer.handle_event(wheel(vec2(400.0, 300.0), 30.0, Modifiers::NONE));
+        zoomer.update(SETTLE_TIME);
+        let uv_center =
+            |zoomer: &Zoomer<HeadlessPlatform>| zoomer.pixel_to_uv_space(vec2(400.0, 300.0));
+        let start = uv_center(&zoomer);
+
+        zoomer.handle_event(key_down(Key::Right, Modifiers::NONE));
+        let mut distances = Vec::new();
+        for _ in 0..2 {
+            let before = uv_center(&zoomer);
+            for _ in 0..30 {
+                zoomer.update(1.0 / 60.0);
+                // Key repeats don't change anything.
+                zoomer.handle_event(key_down(Key::Right, Modifiers::NONE));
+            }
+            distances.push(uv_center(&zoomer).x - before.x);
+        }
+        zoomer.handle_event(InputEvent::KeyUp {
+            key: Key::Right,
+            modifiers: Modifiers::NONE,
+        });
+        zoomer.update(SETTLE_TIME);
+
+        assert!(distances[0] > 0.0);
+        assert!(distances[1] > distances[0] * 1.5, "{:?}", distances);
+        assert!(uv_center(&zoomer).x > start.x + 0.1);
+        assert!((uv_center(&zoomer).y - start.y).abs() < 1e-4);
+
+        // Ctrl + S exports the view instead of panning down, which would stop panning up.
+        zoomer.handle_event(key_down(Key::Letter('W'), Modifiers::NONE));
+        zoomer.handle_event(key_down(
+            Key::Letter('S'),
+            Modifiers {
+                ctrl: true,
+                ..Modifiers::NONE
+            },
+        ));
+        assert_eq!(zoomer.key_pan_direction(), vec2(0.0, 1.0));
+        assert!(zoomer.view_export_requested);
+    }
+
+    #[test]
+    fn auto_repeated_releases_do_not_slow_key_panning_down() {
+        let mut zoomer = zoomer();
+        zoomer.handle_event(wheel(vec2(400.0, 300.0), 30.0, Modifiers::NONE));
+        zoomer.update(SETTLE_TIME);
+        let uv_center =
+            |zoomer: &Zoomer<HeadlessPlatform>| zoomer.pixel_to_uv_space(vec2(400.0, 300.0));
+
+        zoomer.handle_event(key_down(Key::Left, Modifiers::NONE));
+        let mut distances = Vec::new();
+        for _ in 0..2 {
+            let before = uv_center(&zoomer);
+            for _ in 0..30 {
+                zoomer.update(1.0 / 60.0);
+                zoomer.handle_event(InputEvent::KeyUp {
+                    key: Key::Left,
+                    modifiers: Modifiers::NONE,
+                });
+                zoomer.handle_event(key_down(Key::Left, Modifiers::NONE));
+            }
+            distances.push(before.x - uv_center(&zoomer).x);
+        }
+
+        assert!(distances[0] > 0.0);
+        assert!(distances[1] > distances[0] * 1.5, "{:?}", distances);
+        assert!(zoomer.key_pan_speed.current() > KEY_PAN_SPEED + KEY_PAN_ACCELERATION * 0.9);
+    }
+
+    #[test]
+    fn plus_and_minus_zoom_around_the_center_or_the_cursor() {
+        let mut zoomer = zoomer();
+        let cursor = vec2(600.0, 150.0);
+        zoomer.handle_event(InputEvent::PointerMove {
+            position: cursor,
+            buttons: MouseButtons::default(),
+        });
+        let uv_center = zoomer.pixel_to_uv_space(vec2(400.0, 300.0));
+
+        zoomer.handle_event(key_down(Key::Plus, Modifiers::NONE));
+        zoomer.handle_event(key_down(Key::Plus, Modifiers::NONE));
+        zoomer.handle_event(key_down(Key::Minus, Modifiers::NONE));
+        zoomer.update(SETTLE_TIME);
+
+        assert!((zoomer.camera.as_ref().unwrap().zoom_factor() - 1.25).abs() < 1e-4);
+        assert_vec2_eq(zoomer.pixel_to_uv_space(vec2(400.0, 300.0)), uv_center);
+
+        let uv_cursor = zoomer.pixel_to_uv_space(cursor);
+        zoomer.handle_event(key_down(
+            Key::Plus,
+            Modifiers {
+                ctrl: true,
+                ..Modifiers::NONE
+            },
+        ));
+        zoomer.update(SETTLE_TIME);
+
+        assert!((zoomer.camera.as_ref().unwrap().zoom_factor() - 1.5625).abs() < 1e-4);
+        assert_vec2_eq(zoomer.pixel_to_uv_space(cursor), uv_cursor);
+    }
+
+    #[test]
+    fn zero_fits_the_capture_and_shift_one_shows_actual_pixels() {
+        let mut zoomer = two_monitor_zoomer(HeadlessPlatform::new(800, 600), ZoomMode::Window);
+        zoomer.init();
+        zoomer.handle_event(wheel(vec2(100.0, 100.0), 20.0, Modifiers::NONE));
+        zoomer.handle_event(wheel(
+            vec2(100.0, 100.0),
+            4.0,
+            Modifiers {
+                shift: true,
+                ..Modifiers::NONE
+            },
+        ));
+        zoomer.update(SETTLE_TIME);
+
+        zoomer.handle_event(key_down(
+            Key::Digit(1),
+            Modifiers {
+                shift: true,
+                ..Modifiers::NONE
+            },
+        ));
+        zoomer.update(SETTLE_TIME);
+
+        // The 260x120 capture is shown at 1:1, still rotated.
+        let camera = zoomer.camera.as_ref().unwrap();
+        assert!((camera.zoom_factor() - 260.0 / 800.0).abs() < 1e-4);
+        assert!((camera.rotation().to_degrees() - 20.0).abs() < 1e-3);
+
+        zoomer.handle_event(key_down(Key::Digit(0), Modifiers::NONE));
+        zoomer.update(SETTLE_TIME);
+
+        // The capture is relatively wider than the window, so it fits at the window's width.
+        let camera = zoomer.camera.as_ref().unwrap();
+        assert!((camera.zoom_factor() - 1.0).abs() < 1e-4);
+        assert!(camera.rotation().abs() < 1e-4);
+        assert_vec2_eq(zoomer.pixel_to_uv_space(vec2(0.0, 300.0)), vec2(0.0, 0.5));
+        assert_vec2_eq(zoomer.pixel_to_uv_space(vec2(800.0, 300.0)), vec2(1.0, 0.5));
+    }
+
+    #[test]
+    fn ctrl_z_and_ctrl_y_undo_and_redo_camera_moves() {
+        let mut zoomer = zoomer();
+        let ctrl = Modifiers {
+            ctrl: true,
+            ..Modifiers::NONE
+        };
+        let uv_center =
+            |zoomer: &Zoomer<HeadlessPlatform>| zoomer.pixel_to_uv_space(vec2(400.0, 300.0));
+
+        zoomer.handle_event(wheel(vec2(600.0, 150.0), 5.0, Modifiers::NONE));
+        zoomer.update(SETTLE_TIME);
+        let zoomed = uv_center(&zoomer);
+
+        // Holding still halfway through a drag doesn't count as settling.
+        let left = MouseButtons {
+            left: true,
+            ..MouseButtons::default()
+        };
+        zoomer.handle_event(InputEvent::PointerDown {
+            position: vec2(200.0, 300.0),
+            button: MouseButton::Left,
+        });
+        for position in [vec2(250.0, 300.0), vec2(300.0, 350.0)] {
+            zoomer.handle_event(InputEvent::PointerMove {
+                position,
+                buttons: left,
+            });
+            zoomer.update(SETTLE_TIME);
+        }
+        zoomer.handle_event(InputEvent::PointerUp {
+            position: vec2(300.0, 350.0),
+            button: MouseButton::Left,
+        });
+        zoomer.update(SETTLE_TIME);
+        let dragged = uv_center(&zoomer);
+
+        zoomer.handle_event(key_down(Key::Letter('Z'), ctrl));
+        zoomer.update(SETTLE_TIME);
+        assert_vec2_eq(uv_center(&zoomer), zoomed);
+        assert!(!zoomer.box_zoom_key_is_down);
+
+        zoomer.handle_event(key_down(Key::Letter('Z'), ctrl));
+        zoomer.update(SETTLE_TIME);
+        assert_eq!(zoomer.camera.as_ref().unwrap().zoom_factor(), 1.0);
+
+        zoomer.handle_event(key_down(Key::Letter('Y'), ctrl));
+        zoomer.handle_event(key_down(
+            Key::Letter('Z'),
+            Modifiers {
+                shift: true,
+                ..ctrl
+            },
+        ));
+        zoomer.update(SETTLE_TIME);
+        assert_vec2_eq(uv_center(&zoomer), dragged);
+    }
+
+    #[test]
+    fn clicking_and_dragging_on_the_minimap_moves_the_camera() {
+        let mut zoomer = zoomer();
+        let left = MouseButtons {
+            left: true,
+            ..MouseButtons::default()
+        };
+        let uv_center =
+            |zoomer: &Zoomer<HeadlessPlatform>| zoomer.pixel_to_uv_space(vec2(400.0, 300.0));
+        zoomer.handle_event(wheel(vec2(400.0, 300.0), 30.0, Modifiers::NONE));
+        zoomer.update(SETTLE_TIME);
+
+        // 240x180 pixels in the bottom right corner.
+        zoomer.handle_event(key_down(Key::Letter('M'), Modifiers::NONE));
+        zoomer.handle_event(InputEvent::PointerDown {
+            position: vec2(608.0, 453.0),
+            button: MouseButton::Left,
+        });
+        zoomer.update(SETTLE_TIME);
+        assert_vec2_eq(uv_center(&zoomer), vec2(0.25, 0.25));
+
+        zoomer.handle_event(InputEvent::PointerMove {
+            position: vec2(728.0, 498.0),
+            buttons: left,
+        });
+        zoomer.update(SETTLE_TIME);
+        zoomer.handle_event(InputEvent::PointerUp {
+            position: vec2(728.0, 498.0),
+            button: MouseButton::Left,
+        });
+        zoomer.update(SETTLE_TIME);
+
+        assert_vec2_eq(uv_center(&zoomer), vec2(0.75, 0.5));
+        assert!((zoomer.camera.as_ref().unwrap().zoom_factor() - 4.0).abs() < 1e-3);
+
+        // Once hidden, the same spot pans.
+        zoomer.handle_event(key_down(Key::Letter('M'), Modifiers::NONE));
+        drag(&mut zoomer, vec2(608.0, 453.0), vec2(-5.0, 0.0), 10, 0.25);
+        zoomer.update(SETTLE_TIME);
+        assert!(uv_center(&zoomer).x > 0.75 + 0.01);
+    }
+
+    #[test]
+    fn p_snaps_to_whole_pixels_once_settled() {
+        let mut zoomer = two_monitor_zoomer(HeadlessPlatform::new(800, 600), ZoomMode::Window);
+        zoomer.init();
+        // The 260x120 capture starts out at 800 / 260 screen pixels per pixel.
+        assert!((zoomer.magnification() - 800.0 / 260.0).abs() < 1e-4);
+
+        zoomer.handle_event(key_down(Key::Letter('P'), Modifiers::NONE));
+        zoomer.handle_event(wheel(vec2(613.0, 217.0), 4.0, Modifiers::NONE));
+        // Not while the zoom is still going.
+        zoomer.update(0.1);
+        assert!((zoomer.camera.as_ref().unwrap().view().zoom_factor - 1.4).abs() < 1e-4);
+
+        for _ in 0..10 {
+            zoomer.update(1.0);
+        }
+
+        assert!((zoomer.magnification() - 4.0).abs() < 1e-3);
+        // The capture's pixels are 4 screen pixels wide and tall, starting on a screen pixel.
+        let corner = -zoomer
+            .pixel_to_uv_space(vec2(0.0, 0.0))
+            .component_mul(&vec2(260.0, 120.0))
+            * 4.0;
+        assert_vec2_eq(corner, corner.map(f32::round));
+
+        // Rotated, it can't snap.
+        zoomer.handle_event(wheel(
+            vec2(400.0, 300.0),
+            1.0,
+            Modifiers {
+                shift: true,
+                ..Modifiers::NONE
+            },
+        ));
+        zoomer.handle_event(wheel(vec2(613.0, 217.0), 1.0, Modifiers::NONE));
+        zoomer.update(SETTLE_TIME);
+        zoomer.update(SETTLE_TIME);
+        assert!((zoomer.magnification() - 4.4).abs() < 1e-3);
+    }
+
+    #[test]
+    fn hover_without_buttons_does_not_pan() {
+        let mut zoomer = zoomer();
+        let uv_before = zoomer.pixel_to_uv_space(vec2(100.0, 100.0));
+
+        zoomer.handle_event(InputEvent::PointerMove {
+            position: vec2(250.0, 175.0),
+            buttons: MouseButtons::default(),
+        });
+        zoomer.update(SETTLE_TIME);
+
+        assert_eq!(zoomer.mouse_pos, vec2(250.0, 175.0));
+        assert_vec2_eq(zoomer.pixel_to_uv_space(vec2(100.0, 100.0)), uv_before);
+    }
+
+    #[test]
+    fn shift_wheel_rotates_around_cursor() {
+        let mut zoomer = zoomer();
+        let cursor = vec2(600.0, 150.0);
+        let uv_before = zoomer.pixel_to_uv_space(cursor);
+
+        zoomer.handle_event(wheel(
+            cursor,
+            6.0,
+            Modifiers {
+                shift: true,
+                ..Default::default()
+            },
+        ));
+        zoomer.update(SETTLE_TIME);
+
+        let camera = zoomer.camera.as_ref().unwrap();
+        assert!((camera.rotation().to_degrees() - 30.0).abs() < 1e-3);
+        assert_eq!(camera.zoom_factor(), 1.0);
+        assert_vec2_eq(zoomer.pixel_to_uv_space(cursor), uv_before);
+
+        // Moving right on the screen moves down and right in the image, without shearing.
+        let right = zoomer.pixel_to_uv_space(cursor + vec2(100.0, 0.0)) - uv_before;
+        let (sin, cos) = 30f32.to_radians().sin_cos();
+        assert_vec2_eq(right, vec2(cos * 100.0 / 800.0, sin * 100.0 / 600.0));
+    }
+
+    #[test]
+    fn releasing_a_drag_keeps_part_of_the_rotated_capture_on_screen() {
+        let mut zoomer = zoomer();
+
+        zoomer.handle_event(wheel(
+            vec2(400.0, 300.0),
+            9.0,
+            Modifiers {
+                shift: true,
+                ..Default::default()
+            },
+        ));
+        zoomer.update(SETTLE_TIME);
+
+        zoomer.handle_event(InputEvent::PointerDown {
+            position: vec2(100.0, 100.0),
+            button: MouseButton::Left,
+        });
+        for position in [vec2(790.0, 590.0), vec2(1500.0, 1200.0)] {
+            zoomer.handle_event(InputEvent::PointerMove {
+                position,
+                buttons: MouseButtons {
+                    left: true,
+                    ..Default::default()
+                },
+            });
+        }
+        zoomer.handle_event(InputEvent::PointerUp {
+            position: vec2(1500.0, 1200.0),
+            button: MouseButton::Left,
+        });
+        zoomer.update(SETTLE_TIME);
+
+        let is_on_capture = |uv: Vec2| (0.0..=1.0).contains(&uv.x) && (0.0..=1.0).contains(&uv.y);
+        let visible_pixels = (0..=80)
+            .flat_map(|x| (0..=60).map(move |y| vec2(x as f32 * 10.0, y as f32 * 10.0)))
+            .filter(|&pixel| is_on_capture(zoomer.pixel_to_uv_space(pixel)))
+            .count();
+
+        assert!(visible_pixels > 0);
+        // Only just, the capture ends up in the top left corner.
+        assert!(!is_on_capture(zoomer.pixel_to_uv_space(vec2(100.0, 100.0))));
+    }
+
+    #[test]
+    fn ctrl_wheel_resizes_enabled_highlighter() {
+        let mut zoomer = zoomer();
+        assert_eq!(zoomer.highlighter.radius(), f32::INFINITY);
+
+        zoomer.handle_event(key_down(Key::Letter('C'), Modifiers::NONE));
+        zoomer.handle_event(wheel(
+            vec2(400.0, 300.0),
+            1.0,
+            Modifiers {
+                ctrl: true,
+                ..Default::default()
+            },
+        ));
+        zoomer.update(SETTLE_TIME);
+
+        assert!((zoomer.highlighter.radius() - 60.0).abs() < 1e-3);
+        assert_eq!(zoomer.camera.as_ref().unwrap().zoom_factor(), 1.0);
+    }
+
+    #[test]
+    fn open_image_replaces_capture() {
+        let mut zoomer = zoomer();
+
+        let path = std::env::temp_dir().join("zoomer_open_image_replaces_capture.ppm");
+        fs::write(&path, b"P3 4 1 255 0 0 255 0 0 255 0 0 255 0 0 255").unwrap();
+
+        zoomer.open_image(path).unwrap();
+        zoomer.handle_event(key_down(Key::Escape, Modifiers::NONE));
+        zoomer.handle_event(InputEvent::Hotkey);
+
+        // The image is reloaded instead of capturing the screen when the zoomer is reopened.
+        let screenshot = zoomer.screenshot.as_ref().unwrap();
+        assert_eq!((screenshot.width(), screenshot.height()), (4, 1));
+        assert_eq!(zoomer.platform.capture_count, 1);
+    }
+
+    #[test]
+    fn unreadable_image_falls_back_to_capture() {
+        let mut zoomer = Zoomer::new(HeadlessPlatform::new(800, 600));
+        zoomer.set_image_path(Some(PathBuf::from("/nonexistent/image.png")));
+
+        zoomer.init();
+
+        assert_eq!(zoomer.platform.capture_count, 1);
+        assert!(zoomer
+            .open_image(PathBuf::from("/nonexistent/image.png"))
+            .is_err());
+    }
+
+    #[test]
+    fn resize_updates_client_size() {
+        let mut zoomer = zoomer();
+
+        zoomer.handle_event(InputEvent::Resize {
+            width: 1024,
+            height: 768,
+        });
+
+        assert_eq!((zoomer.client_width, zoomer.client_height), (1024, 768));
+    }
+
+    #[test]
+    fn resizing_moves_the_clamp_bounds_to_the_capture() {
+        let mut zoomer = zoomer();
+        zoomer.set_clamp_policy(ClampPolicy::KeepViewInsideImage);
+
+        // Twice as wide, so the capture fills the width and only the middle half of its height fits.
+        zoomer.handle_event(InputEvent::Resize {
+            width: 1600,
+            height: 600,
+        });
+        zoomer.update(SETTLE_TIME);
+        assert_vec2_eq(zoomer.pixel_to_uv_space(vec2(0.0, 0.0)), vec2(0.0, 0.25));
+
+        drag(&mut zoomer, vec2(800.0, 100.0), vec2(0.0, 20.0), 20, 0.5);
+        zoomer.update(SETTLE_TIME);
+
+        // Stops at the top edge of the capture rather than where it was before the resize.
+        assert_vec2_eq(zoomer.pixel_to_uv_space(vec2(0.0, 0.0)), vec2(0.0, 0.0));
+        assert_vec2_eq(
+            zoomer.pixel_to_uv_space(vec2(1600.0, 600.0)),
+            vec2(1.0, 0.5),
+        );
+    }
+
+    #[test]
+    fn export_file_names_are_timestamped() {
+        let time = UNIX_EPOCH + std::time::Duration::from_millis(1_709_217_045_678);
+
+        assert_eq!(
+            export_file_name("view", time),
+            "zoomer-view-2024-02-29_14-30-45-678.png"
+        );
+        assert_eq!(
+            export_file_name("capture", UNIX_EPOCH),
+            "zoomer-capture-1970-01-01_00-00-00-000.png"
+        );
+    }
+
+    #[test]
+    fn ctrl_shift_s_exports_the_full_capture() {
+        let mut zoomer = zoomer();
+
+        let export_dir = std::env::temp_dir().join("zoomer_ctrl_shift_s_exports_the_full_capture");
+        let _ = fs::remove_dir_all(&export_dir);
+        zoomer.set_export_dir(export_dir.clone());
+
+        // Zooming doesn't affect the exported capture.
+        zoomer.handle_event(wheel(vec2(400.0, 300.0), 5.0, Modifiers::NONE));
+        zoomer.handle_event(key_down(
+            Key::Letter('S'),
+            Modifiers {
+                ctrl: true,
+                shift: true,
+                ..Default::default()
+            },
+        ));
+
+        let exports: Vec<_> = fs::read_dir(&export_dir).unwrap().collect();
+        assert_eq!(exports.len(), 1);
+
+        let export = image::load(&exports[0].as_ref().unwrap().path()).unwrap();
+        assert_eq!((export.width(), export.height()), (800, 600));
+        assert!(zoomer.export_status.as_ref().unwrap().starts_with("Saved"));
+    }
+
+    #[test]
+    fn captures_the_bounding_rect_of_stacked_monitors() {
+        let mut platform = HeadlessPlatform::new(800, 600);
+        platform.monitors = vec![
+            Monitor {
+                x: 0,
+                y: 0,
+                width: 300,
+                height: 200,
+                ..platform.monitors[0].clone()
+            },
+            Monitor {
+                x: 100,
+                y: 200,
+                width: 100,
+                height: 100,
+                ..platform.monitors[0].clone()
+            },
+        ];
+
+        let mut zoomer = Zoomer::new(platform);
+        zoomer.set_background_color([0x10, 0x20, 0x30, 0xFF]);
+        zoomer.init();
+
+        let screenshot = zoomer.screenshot.as_ref().unwrap();
+        assert_eq!((screenshot.width(), screenshot.height()), (300, 300));
+
+        // Both monitors are captured, the corners beside the lower monitor are filled with the background.
+        assert_pixel_eq(screenshot, 0, 0, [0xFF, 0x00, 0xFF, 0xFF]);
+        assert_pixel_eq(screenshot, 150, 250, [0xFF, 0x00, 0xFF, 0xFF]);
+        assert_pixel_eq(screenshot, 50, 250, [0x10, 0x20, 0x30, 0xFF]);
+        assert_pixel_eq(screenshot, 250, 299, [0x10, 0x20, 0x30, 0xFF]);
+    }
+
+    /// A zoomer on a desktop with a small monitor left of a 160x120 one, the cursor is on the latter.
+    fn two_monitor_zoomer(
+        platform: HeadlessPlatform,
+        zoom_mode: ZoomMode,
+    ) -> Zoomer<HeadlessPlatform> {
+        let mut platform = platform;
+        platform.monitors = vec![
+            Monitor {
+                x: -100,
+                y: 20,
+                width: 100,
+                height: 80,
+                ..platform.monitors[0].clone()
+            },
+            Monitor {
+                x: 0,
+                y: 0,
+                width: 160,
+                height: 120,
+                ..platform.monitors[0].clone()
+            },
+        ];
+        platform.desktop_pixel = |x, y| [x as u8, y as u8, 0x80, 0xFF];
+        platform.cursor_position = (50, 60);
+
+        let mut zoomer = Zoomer::new(platform);
+        zoomer.set_zoom_mode(zoom_mode);
+
+        zoomer
+    }
+
+    /// Renders a frame and asserts that it shows the desktop of [`two_monitor_zoomer`] starting at
+    /// (`start_x`, `start_y`) at 1:1, with the background wherever there is no monitor.
+    #[cfg(target_os = "linux")]
+    fn assert_renders_desktop_at_one_to_one(
+        zoomer: &mut Zoomer<HeadlessPlatform>,
+        start_x: i32,
+        start_y: i32,
+    ) {
+        let image = zoomer.render_to_image();
+
+        for y in 0..image.height() {
+            for x in 0..image.width() {
+                let (desktop_x, desktop_y) = (start_x + x as i32, start_y + y as i32);
+
+                let is_on_monitor = zoomer
+                    .platform
+                    .monitors
+                    .iter()
+                    .any(|monitor| monitor.contains(desktop_x, desktop_y));
+
+                let expected = if is_on_monitor {
+                    [desktop_x as u8, desktop_y as u8, 0x80, 0xFF]
+                } else {
+                    zoomer.background_color
+                };
+
+                assert_pixel_eq(&image, x, y, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn overlay_covers_all_monitors() {
+        let mut zoomer = two_monitor_zoomer(HeadlessPlatform::new(800, 600), ZoomMode::Overlay);
+        zoomer.init();
+
+        assert_eq!(
+            zoomer.platform.window_mode,
+            WindowMode::Borderless {
+                x: -100,
+                y: 0,
+                width: 260,
+                height: 120
+            }
+        );
+        assert_eq!((zoomer.client_width, zoomer.client_height), (260, 120));
+        assert_vec2_eq(zoomer.pixel_to_uv_space(vec2(0.0, 0.0)), vec2(0.0, 0.0));
+        assert_vec2_eq(zoomer.pixel_to_uv_space(vec2(260.0, 120.0)), vec2(1.0, 1.0));
+
+        // Reopening starts over at 1:1.
+        zoomer.handle_event(wheel(vec2(80.0, 60.0), 5.0, Modifiers::NONE));
+        zoomer.handle_event(key_down(Key::Escape, Modifiers::NONE));
+        zoomer.handle_event(InputEvent::Hotkey);
+
+        assert_eq!(zoomer.camera.as_ref().unwrap().zoom_factor(), 1.0);
+    }
+
+    #[test]
+    fn window_mode_keeps_the_window_and_camera() {
+        let mut zoomer = two_monitor_zoomer(HeadlessPlatform::new(800, 600), ZoomMode::Window);
+        zoomer.init();
+
+        assert_eq!(zoomer.platform.window_mode, WindowMode::Windowed);
+        assert_eq!((zoomer.client_width, zoomer.client_height), (800, 600));
+
+        zoomer.handle_event(wheel(vec2(400.0, 300.0), 5.0, Modifiers::NONE));
+        zoomer.update(SETTLE_TIME);
+        zoomer.handle_event(key_down(Key::Escape, Modifiers::NONE));
+        zoomer.handle_event(InputEvent::Hotkey);
+
+        assert!((zoomer.camera.as_ref().unwrap().zoom_factor() - 1.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn window_mode_fits_the_clamp_bounds_to_a_recapture() {
+        let mut zoomer = two_monitor_zoomer(HeadlessPlatform::new(800, 600), ZoomMode::Window);
+        let left_monitor = zoomer.platform.monitors.remove(0);
+        zoomer.set_clamp_policy(ClampPolicy::KeepViewInsideImage);
+        zoomer.init();
+
+        zoomer.handle_event(wheel(vec2(400.0, 300.0), 5.0, Modifiers::NONE));
+        zoomer.update(SETTLE_TIME);
+        zoomer.handle_event(key_down(Key::Escape, Modifiers::NONE));
+
+        // Plugging the left monitor back in makes the capture wider, so it no longer fills the window's height.
+        zoomer.platform.monitors.insert(0, left_monitor);
+        zoomer.handle_event(InputEvent::Hotkey);
+        assert_eq!(zoomer.screenshot.as_ref().unwrap().width(), 260);
+
+        drag(&mut zoomer, vec2(400.0, 100.0), vec2(0.0, 20.0), 20, 0.5);
+        zoomer.update(SETTLE_TIME);
+
+        // The view is taller than the capture, so it's kept centered on it.
+        assert_vec2_eq(zoomer.pixel_to_uv_space(vec2(400.0, 300.0)), vec2(0.5, 0.5));
+    }
+
+    #[test]
+    fn monitor_mode_covers_the_monitor_under_the_cursor() {
+        let mut zoomer = two_monitor_zoomer(HeadlessPlatform::new(800, 600), ZoomMode::Monitor);
+        zoomer.init();
+
+        assert_eq!(
+            zoomer.platform.window_mode,
+            WindowMode::Borderless {
+                x: 0,
+                y: 0,
+                width: 160,
+                height: 120
+            }
+        );
+        assert_eq!((zoomer.client_width, zoomer.client_height), (160, 120));
+        assert_vec2_eq(zoomer.pixel_to_uv_space(vec2(0.0, 0.0)), vec2(0.0, 0.0));
+        assert_vec2_eq(zoomer.pixel_to_uv_space(vec2(160.0, 120.0)), vec2(1.0, 1.0));
+
+        // Reopening on the other monitor captures that one and starts over at 1:1.
+        zoomer.handle_event(wheel(vec2(80.0, 60.0), 5.0, Modifiers::NONE));
+        zoomer.handle_event(key_down(Key::Escape, Modifiers::NONE));
+        zoomer.platform.cursor_position = (-50, 50);
+        zoomer.handle_event(InputEvent::Hotkey);
+
+        let screenshot = zoomer.screenshot.as_ref().unwrap();
+        assert_eq!((screenshot.width(), screenshot.height()), (100, 80));
+        assert_pixel_eq(screenshot, 0, 0, [(-100i32) as u8, 20, 0x80, 0xFF]);
+        assert_eq!(
+            zoomer.platform.window_mode,
+            WindowMode::Borderless {
+                x: -100,
+                y: 20,
+                width: 100,
+                height: 80
+            }
+        );
+        assert_eq!(zoomer.camera.as_ref().unwrap().zoom_factor(), 1.0);
+        assert_vec2_eq(zoomer.pixel_to_uv_space(vec2(100.0, 80.0)), vec2(1.0, 1.0));
+    }
+
+    #[cfg(target_os = "linux")]
+    fn rendering_zoomer(width: u32, height: u32) -> Zoomer<HeadlessPlatform> {
+        let mut zoomer = Zoomer::new(HeadlessPlatform::with_opengl(width, height));
+        zoomer.init();
+        zoomer.init_renderer();
+
+        zoomer
+    }
+
+    fn assert_pixel_eq(image: &Screenshot, x: u32, y: u32, expected: [u8; 4]) {
+        let index = (y * image.stride() + x * Screenshot::BYTES_PER_PIXEL) as usize;
+        let actual = &image.pixel_bytes()[index..index + Screenshot::BYTES_PER_PIXEL as usize];
+
+        assert!(
+            actual
+                .iter()
+                .zip(expected)
+                .all(|(&a, b)| (a as i32 - b as i32).abs() <= 2),
+            "pixel ({}, {}) is {:?}, expected {:?}",
+            x,
+            y,
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn renders_capture_offscreen() {
+        let mut zoomer = rendering_zoomer(160, 120);
+
+        let image = zoomer.render_to_image();
+
+        assert_eq!((image.width(), image.height()), (160, 120));
+        assert_eq!(zoomer.platform.swap_count, 1);
+
+        for (x, y) in [(0, 0), (159, 0), (80, 60), (0, 119), (159, 119)] {
+            assert_pixel_eq(&image, x, y, [0xFF, 0x00, 0xFF, 0xFF]);
+        }
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn renders_highlighter_offscreen() {
+        let mut zoomer = rendering_zoomer(160, 120);
+
+        zoomer.handle_event(key_down(Key::Letter('C'), Modifiers::NONE));
+        zoomer.highlighter.set_radius(20.0);
+        zoomer.handle_event(InputEvent::PointerMove {
+            position: vec2(40.0, 30.0),
+            buttons: MouseButtons::default(),
+        });
+
+        let image = zoomer.render_to_image();
+
+        // Slightly brightened inside of the highlighter, darkened outside.
+        assert_pixel_eq(&image, 40, 30, [0xFF, 0x09, 0xFF, 0xFF]);
+        assert_pixel_eq(&image, 120, 90, [0x73, 0x00, 0x73, 0xFF]);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn monitor_mode_opens_at_one_to_one() {
+        let mut zoomer =
+            two_monitor_zoomer(HeadlessPlatform::with_opengl(160, 120), ZoomMode::Monitor);
+        zoomer.init();
+        zoomer.init_renderer();
+
+        assert_renders_desktop_at_one_to_one(&mut zoomer, 0, 0);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn overlay_opens_at_one_to_one() {
+        let mut zoomer =
+            two_monitor_zoomer(HeadlessPlatform::with_opengl(260, 120), ZoomMode::Overlay);
+        zoomer.set_background_color([0x10, 0x20, 0x30, 0xFF]);
+        zoomer.init();
+        zoomer.init_renderer();
+
+        assert_renders_desktop_at_one_to_one(&mut zoomer, -100, 0);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn ctrl_s_exports_the_rendered_view() {
+        let mut zoomer = rendering_zoomer(160, 120);
+
+        let export_dir = std::env::temp_dir().join("zoomer_ctrl_s_exports_the_rendered_view");
+        let _ = fs::remove_dir_all(&export_dir);
+        zoomer.set_export_dir(export_dir.clone());
+
+        zoomer.handle_event(key_down(Key::Letter('C'), Modifiers::NONE));
+        zoomer.highlighter.set_radius(20.0);
+        zoomer.handle_event(key_down(
+            Key::Letter('S'),
+            Modifiers {
+                ctrl: true,
+                ..Default::default()
+            },
+        ));
+        zoomer.render();
+
+        let exports: Vec<_> = fs::read_dir(&export_dir).unwrap().collect();
+        assert_eq!(exports.len(), 1);
+
+        let export = image::load(&exports[0].as_ref().unwrap().path()).unwrap();
+        assert_eq!((export.width(), export.height()), (160, 120));
+        assert_pixel_eq(&export, 120, 90, [0x73, 0x00, 0x73, 0xFF]);
+        assert!(!zoomer.view_export_requested);
+    }
 }